@@ -0,0 +1,106 @@
+use crate::rpc::to_snake_case;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+
+pub(crate) const ASYNC_DISABLED: &str =
+    "the async keyword requires the `async` feature of wasmcloud-actor-core";
+
+pub(crate) fn expand(operation: syn::Path, item: syn::ItemFn) -> syn::Result<TokenStream> {
+    let (handler, blocking) = match &item.sig.asyncness {
+        Some(asyncness) if !cfg!(feature = "async") => {
+            return Err(syn::Error::new_spanned(asyncness, ASYNC_DISABLED));
+        }
+        Some(_) => {
+            let (ident, shim) = blocking_shim(&item.sig)?;
+            (ident, Some(shim))
+        }
+        None => (item.sig.ident.clone(), None),
+    };
+
+    let register = register_call(&operation, &handler)?;
+    let register_fn = format_ident!("__register_{}", item.sig.ident);
+
+    Ok(quote! {
+        #item
+
+        #blocking
+
+        #[doc(hidden)]
+        fn #register_fn() {
+            #register;
+        }
+
+        wasmcloud_actor_core::__private::inventory::submit! {
+            wasmcloud_actor_core::__private::Registration(#register_fn)
+        }
+    })
+}
+
+/// Generates a synchronous function that runs an `async fn` handler to completion, so that it
+/// can be registered like any other handler
+fn blocking_shim(sig: &syn::Signature) -> syn::Result<(syn::Ident, TokenStream)> {
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "async handlers cannot be generic",
+        ));
+    }
+    let types = sig
+        .inputs
+        .iter()
+        .map(|input| match input {
+            syn::FnArg::Typed(t) => Ok(&t.ty),
+            syn::FnArg::Receiver(r) => Err(syn::Error::new_spanned(r, "unexpected receiver")),
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let args = (0..types.len())
+        .map(|i| format_ident!("__arg{}", i))
+        .collect::<Vec<_>>();
+
+    let name = &sig.ident;
+    let shim = format_ident!("__{}_blocking", name, span = name.span());
+    let output = &sig.output;
+    let tokens = quote! {
+        #[doc(hidden)]
+        fn #shim(#(#args: #types),*) #output {
+            wasmcloud_actor_core::executor::block_on(#name(#(#args),*))
+        }
+    };
+    Ok((shim, tokens))
+}
+
+/// Turns an operation path such as `http::HandleRequest` into a call to the matching
+/// `http::Handlers::register_handle_request` function. The handler is spanned so that a
+/// signature mismatch is reported on the handler function itself.
+fn register_call(operation: &syn::Path, handler: &syn::Ident) -> syn::Result<TokenStream> {
+    let mut prefix = operation.clone();
+    let op = match prefix.segments.pop() {
+        Some(pair) => pair.into_value(),
+        None => {
+            return Err(syn::Error::new_spanned(
+                operation,
+                "expected an interface operation, e.g. `http::HandleRequest`",
+            ))
+        }
+    };
+    if !op.arguments.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &op.arguments,
+            "interface operations do not take generic arguments",
+        ));
+    }
+
+    let register = format_ident!(
+        "register_{}",
+        to_snake_case(&op.ident.to_string()),
+        span = op.ident.span()
+    );
+    let handlers = if prefix.segments.is_empty() {
+        quote!(Handlers)
+    } else {
+        quote!(#prefix Handlers)
+    };
+    Ok(quote_spanned! {handler.span()=>
+        #handlers::#register(#handler)
+    })
+}
@@ -1,8 +1,16 @@
 use proc_macro::TokenStream;
 use quote::quote;
 
+mod handler;
+mod link_values;
+mod rpc;
+
 /// Marks the actor entry-point function to be executed by wasmcloud
 ///
+/// With the `async` feature of `wasmcloud-actor-core`, the function may be an `async fn`.
+/// Every function of the actor marked with `#[actor::handler]` is registered before the body
+/// of the function runs.
+///
 /// # Examples
 /// ```
 /// #[actor::init]
@@ -12,36 +20,159 @@ use quote::quote;
 /// ```
 #[allow(clippy::needless_doctest_main)]
 #[proc_macro_attribute]
-pub fn init(_: TokenStream, item: TokenStream) -> TokenStream {
+pub fn init(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        let attr = proc_macro2::TokenStream::from(attr);
+        return syn::Error::new_spanned(
+            attr,
+            "`#[actor::init]` takes no arguments, handlers are registered automatically",
+        )
+        .to_compile_error()
+        .into();
+    }
     let mut input = syn::parse_macro_input!(item as syn::ItemFn);
     let attrs = &input.attrs;
 
     let sig = &mut input.sig;
     let body = &input.block;
 
-    if sig.asyncness.is_some() {
-        return syn::Error::new_spanned(
-            sig.fn_token,
-            "the async keyword cannot be used within actors",
-        )
-        .to_compile_error()
-        .into();
-    }
+    let body = match sig.asyncness {
+        Some(asyncness) if !cfg!(feature = "async") => {
+            return syn::Error::new_spanned(asyncness, handler::ASYNC_DISABLED)
+                .to_compile_error()
+                .into();
+        }
+        Some(_) => quote!(wasmcloud_actor_core::executor::block_on(async move #body);),
+        None => quote!(#body),
+    };
 
     sig.asyncness = None;
 
     (quote! {
         #[doc(hidden)]
         fn default_health(_msg: wasmcloud_actor_core::HealthCheckRequest) -> wapc_guest::HandlerResult<wasmcloud_actor_core::HealthCheckResponse> {
-            Ok(wasmcloud_actor_core::HealthCheckResponse::healthy())
+            Ok(wasmcloud_actor_core::health::check())
         }
 
         #(#attrs)*
         #[no_mangle]
         pub fn wapc_init() {
             wasmcloud_actor_core::Handlers::register_health_request(default_health);
+            wasmcloud_actor_core::__private::register_handlers();
             #body
         }
     })
     .into()
 }
+
+/// Marks a function as the handler for an interface operation
+///
+/// The operation is given as a path to the operation name within the interface crate, such
+/// as `http::HandleRequest` or `messaging::HandleMessage`, and the function is registered
+/// through the matching `Handlers::register_*` function from the `wapc_init` generated by
+/// `#[actor::init]`, wherever in the actor the function is declared. A function whose
+/// signature does not match the operation is rejected at compile time. With the `async`
+/// feature of `wasmcloud-actor-core`, the function may be an `async fn`, which is run to
+/// completion for each invocation.
+///
+/// # Examples
+/// ```ignore
+/// #[actor::handler(http::HandleRequest)]
+/// fn handle_request(req: http::Request) -> HandlerResult<http::Response> {
+///     Ok(http::Response::ok())
+/// }
+///
+/// #[actor::handler(messaging::HandleMessage)]
+/// fn handle_message(msg: messaging::BrokerMessage) -> HandlerResult<()> {
+///     Ok(())
+/// }
+///
+/// #[actor::init]
+/// fn init() {}
+/// ```
+#[proc_macro_attribute]
+pub fn handler(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let operation = syn::parse_macro_input!(attr as syn::Path);
+    let input = syn::parse_macro_input!(item as syn::ItemFn);
+    match handler::expand(operation, input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Turns a trait into a typed contract for actor-to-actor calls
+///
+/// Every method of the trait becomes an operation whose name is the method name in
+/// `PascalCase`. Methods must take `&self` followed by any number of owned, serializable
+/// arguments, and return a `HandlerResult`. The arguments are sent as a single map keyed by
+/// argument name.
+///
+/// For a trait named `Greeter`, the macro generates:
+/// * `GreeterClient`, an implementation of the trait that forwards each method to
+///   `call_actor` on the actor it was created for
+/// * `GreeterHandlers::register::<T>()`, which registers one handler per operation that
+///   dispatches incoming calls to `T::default()`
+///
+/// # Examples
+/// ```ignore
+/// #[actor::rpc]
+/// pub trait Greeter {
+///     fn greet(&self, name: String) -> HandlerResult<String>;
+/// }
+///
+/// // In the calling actor
+/// let greeting = GreeterClient::new("greeter").greet("world".to_string())?;
+///
+/// // In the called actor
+/// #[derive(Default)]
+/// struct MyGreeter;
+///
+/// impl Greeter for MyGreeter {
+///     fn greet(&self, name: String) -> HandlerResult<String> {
+///         Ok(format!("Hello, {}!", name))
+///     }
+/// }
+///
+/// #[actor::init]
+/// fn init() {
+///     GreeterHandlers::register::<MyGreeter>();
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn rpc(_: TokenStream, item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as syn::ItemTrait);
+    match rpc::expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derives `FromLinkValues`, parsing the values of a `CapabilityConfiguration` into a struct
+///
+/// Every field is looked up by its name, or by the key given with `#[link_values(rename = "...")]`.
+/// All keys of the struct can be renamed at once with `#[link_values(rename_all = "...")]`, e.g.
+/// `"SCREAMING_SNAKE_CASE"` for environment-style keys. Fields marked with `#[link_values(default)]`
+/// fall back to `Default::default()`, fields marked with `#[link_values(default = "...")]` fall back to
+/// parsing the given value, and `Option` fields are `None` when their key is absent. All other fields
+/// are required.
+///
+/// # Examples
+/// ```ignore
+/// #[derive(FromLinkValues)]
+/// #[link_values(rename_all = "SCREAMING_SNAKE_CASE")]
+/// struct ServerConfig {
+///     port: u16,
+///     #[link_values(default = "30s")]
+///     read_timeout: Duration,
+///     #[link_values(rename = "ALLOWED_ORIGINS", default)]
+///     origins: Vec<String>,
+/// }
+/// ```
+#[proc_macro_derive(FromLinkValues, attributes(link_values))]
+pub fn from_link_values(item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+    match link_values::expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,181 @@
+use crate::rpc::to_pascal_case;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+enum FieldDefault {
+    None,
+    Trait,
+    Value(syn::LitStr),
+}
+
+struct Field {
+    ident: syn::Ident,
+    ty: syn::Type,
+    key: String,
+    default: FieldDefault,
+}
+
+pub(crate) fn expand(input: syn::DeriveInput) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "FromLinkValues cannot be derived for generic types",
+        ));
+    }
+    let named = match &input.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(named),
+            ..
+        }) => named,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "FromLinkValues can only be derived for structs with named fields",
+            ))
+        }
+    };
+
+    let mut rename_all = None;
+    for meta in link_values_attrs(&input.attrs)? {
+        match meta {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) if nv.path.is_ident("rename_all") => {
+                let case = string_lit(&nv.lit)?;
+                if rename(&case.value(), "").is_none() {
+                    return Err(syn::Error::new_spanned(
+                        case,
+                        "unknown case, expected one of \"lowercase\", \"UPPERCASE\", \
+                         \"camelCase\", \"PascalCase\", \"snake_case\", \"kebab-case\", \
+                         \"SCREAMING_SNAKE_CASE\" or \"SCREAMING-KEBAB-CASE\"",
+                    ));
+                }
+                rename_all = Some(case.value());
+            }
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "unknown attribute, expected `rename_all = \"...\"`",
+                ))
+            }
+        }
+    }
+
+    let fields = named
+        .named
+        .iter()
+        .map(|f| field(f, rename_all.as_deref()))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let getters = fields.iter().map(|f| {
+        let Field {
+            ident,
+            ty,
+            key,
+            default,
+        } = f;
+        let get = match default {
+            FieldDefault::None => quote!(__fields.get::<#ty>(#key)),
+            FieldDefault::Trait => quote!(__fields.get_or_default::<#ty>(#key)),
+            FieldDefault::Value(value) => quote!(__fields.get_or::<#ty>(#key, #value)),
+        };
+        quote!(let #ident = #get;)
+    });
+    let idents = fields.iter().map(|f| &f.ident);
+
+    let name = &input.ident;
+    Ok(quote! {
+        impl wasmcloud_actor_core::link::FromLinkValues for #name {
+            fn from_link_values(
+                __values: &std::collections::HashMap<String, String>,
+            ) -> std::result::Result<Self, wasmcloud_actor_core::link::LinkValuesError> {
+                let mut __fields = wasmcloud_actor_core::link::Fields::new(__values);
+                #(#getters)*
+                __fields.finish()?;
+                Ok(#name {
+                    #(#idents: #idents.unwrap()),*
+                })
+            }
+        }
+    })
+}
+
+fn field(f: &syn::Field, rename_all: Option<&str>) -> syn::Result<Field> {
+    let ident = f.ident.clone().unwrap();
+    let name = ident.to_string();
+    let name = name.trim_start_matches("r#");
+    let mut key = match rename_all {
+        Some(case) => rename(case, name).unwrap(),
+        None => name.to_string(),
+    };
+    let mut default = FieldDefault::None;
+
+    for meta in link_values_attrs(&f.attrs)? {
+        match meta {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) if nv.path.is_ident("rename") => {
+                key = string_lit(&nv.lit)?.value();
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) if nv.path.is_ident("default") => {
+                default = FieldDefault::Value(string_lit(&nv.lit)?.clone());
+            }
+            syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("default") => {
+                default = FieldDefault::Trait;
+            }
+            other => return Err(syn::Error::new_spanned(
+                other,
+                "unknown attribute, expected `rename = \"...\"`, `default` or `default = \"...\"`",
+            )),
+        }
+    }
+
+    Ok(Field {
+        ident,
+        ty: f.ty.clone(),
+        key,
+        default,
+    })
+}
+
+fn link_values_attrs(attrs: &[syn::Attribute]) -> syn::Result<Vec<syn::NestedMeta>> {
+    let mut nested = Vec::new();
+    for attr in attrs.iter().filter(|a| a.path.is_ident("link_values")) {
+        match attr.parse_meta()? {
+            syn::Meta::List(list) => nested.extend(list.nested),
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "expected `#[link_values(...)]`",
+                ))
+            }
+        }
+    }
+    Ok(nested)
+}
+
+fn string_lit(lit: &syn::Lit) -> syn::Result<&syn::LitStr> {
+    match lit {
+        syn::Lit::Str(s) => Ok(s),
+        other => Err(syn::Error::new_spanned(other, "expected a string literal")),
+    }
+}
+
+/// Converts a snake_case field name into the given case, or returns `None` if the case is
+/// not supported
+fn rename(case: &str, name: &str) -> Option<String> {
+    Some(match case {
+        "lowercase" => name.replace('_', "").to_lowercase(),
+        "UPPERCASE" => name.replace('_', "").to_uppercase(),
+        "snake_case" => name.to_string(),
+        "SCREAMING_SNAKE_CASE" => name.to_uppercase(),
+        "kebab-case" => name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => name.replace('_', "-").to_uppercase(),
+        "PascalCase" => to_pascal_case(name),
+        "camelCase" => {
+            let pascal = to_pascal_case(name);
+            let mut chars = pascal.chars();
+            match chars.next() {
+                Some(c) => c.to_lowercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        }
+        _ => return None,
+    })
+}
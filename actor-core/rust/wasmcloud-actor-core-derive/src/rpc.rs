@@ -0,0 +1,253 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+
+struct Operation {
+    method: syn::Ident,
+    name: String,
+    args_struct: syn::Ident,
+    arg_names: Vec<syn::Ident>,
+    arg_types: Vec<syn::Type>,
+    output: syn::ReturnType,
+}
+
+pub(crate) fn expand(item: syn::ItemTrait) -> syn::Result<TokenStream> {
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.generics,
+            "actor RPC traits cannot be generic",
+        ));
+    }
+
+    let ops = item
+        .items
+        .iter()
+        .filter_map(|i| match i {
+            syn::TraitItem::Method(m) => Some(operation(&m.sig)),
+            _ => None,
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let trait_name = &item.ident;
+    let vis = &item.vis;
+    let client = format_ident!("{}Client", trait_name);
+    let handlers = format_ident!("{}Handlers", trait_name);
+    let module = format_ident!("__{}_rpc", to_snake_case(&trait_name.to_string()));
+
+    let client_doc = format!(
+        "Client stub that invokes the operations of [{}] on another actor via `call_actor`",
+        trait_name
+    );
+    let handlers_doc = format!(
+        "Registers an implementation of [{}] to handle incoming actor-to-actor calls",
+        trait_name
+    );
+
+    let client_methods = ops.iter().map(|op| {
+        let Operation {
+            method,
+            name,
+            args_struct,
+            arg_names,
+            arg_types,
+            output,
+        } = op;
+        quote! {
+            fn #method(&self, #(#arg_names: #arg_types),*) #output {
+                let args = #module::#args_struct {
+                    #(#arg_names,)*
+                    __traceparent: wasmcloud_actor_core::trace::current().map(|ctx| ctx.to_string()),
+                };
+                wasmcloud_actor_core::call_actor(&self.actor_ref, #name, &args)
+            }
+        }
+    });
+
+    let registrations = ops.iter().map(|op| {
+        let Operation { method, name, .. } = op;
+        quote! {
+            wasmcloud_actor_core::register_function(#name, #module::#method::<T>);
+        }
+    });
+
+    let wrappers = ops.iter().map(|op| {
+        let Operation {
+            method,
+            name,
+            args_struct,
+            arg_names,
+            arg_types,
+            ..
+        } = op;
+        quote! {
+            #[derive(
+                wasmcloud_actor_core::__private::serde::Serialize,
+                wasmcloud_actor_core::__private::serde::Deserialize
+            )]
+            #[serde(crate = "wasmcloud_actor_core::__private::serde")]
+            pub(super) struct #args_struct {
+                #(pub(super) #arg_names: #arg_types,)*
+                /// The trace context of the caller
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                pub(super) __traceparent: Option<String>,
+            }
+
+            pub(super) fn #method<T: #trait_name + Default>(
+                input_payload: &[u8],
+            ) -> wasmcloud_actor_core::__private::wapc_guest::CallResult {
+                wasmcloud_actor_core::interceptor::invocation(#name, input_payload, |input_payload| {
+                    let input = wasmcloud_actor_core::deserialize::<#args_struct>(input_payload)?;
+                    if let Some(traceparent) = &input.__traceparent {
+                        wasmcloud_actor_core::trace::continue_trace(traceparent);
+                    }
+                    let result = T::default().#method(#(input.#arg_names),*)?;
+                    wasmcloud_actor_core::serialize(result)
+                })
+            }
+        }
+    });
+
+    Ok(quote! {
+        #item
+
+        #[doc = #client_doc]
+        #[derive(Debug, Clone)]
+        #vis struct #client {
+            actor_ref: String,
+        }
+
+        impl #client {
+            /// Creates a client for the actor identified by the given reference. This
+            /// reference can be an OCI image URL, a 56-character public key (subject), or,
+            /// if one is defined, a developer-friendly call alias
+            #vis fn new(actor_ref: &str) -> Self {
+                #client {
+                    actor_ref: actor_ref.to_string(),
+                }
+            }
+        }
+
+        impl #trait_name for #client {
+            #(#client_methods)*
+        }
+
+        #[doc = #handlers_doc]
+        #vis struct #handlers {}
+
+        impl #handlers {
+            /// Registers one handler per trait operation. Each incoming call is dispatched
+            /// to a fresh `T::default()` instance
+            #vis fn register<T: #trait_name + Default>() {
+                #(#registrations)*
+            }
+        }
+
+        #[doc(hidden)]
+        mod #module {
+            use super::*;
+
+            #(#wrappers)*
+        }
+    })
+}
+
+fn operation(sig: &syn::Signature) -> syn::Result<Operation> {
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "the async keyword cannot be used within actors",
+        ));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "actor RPC operations cannot be generic",
+        ));
+    }
+
+    let mut inputs = sig.inputs.iter();
+    match inputs.next() {
+        Some(syn::FnArg::Receiver(r)) if r.reference.is_some() && r.mutability.is_none() => {}
+        _ => {
+            return Err(syn::Error::new(
+                sig.paren_token.span,
+                "actor RPC operations must take `&self` as their first argument",
+            ))
+        }
+    }
+
+    let mut arg_names = Vec::new();
+    let mut arg_types = Vec::new();
+    for input in inputs {
+        let pat_type = match input {
+            syn::FnArg::Typed(t) => t,
+            syn::FnArg::Receiver(r) => {
+                return Err(syn::Error::new_spanned(r, "unexpected receiver"));
+            }
+        };
+        let name = match &*pat_type.pat {
+            syn::Pat::Ident(p) => p.ident.clone(),
+            p => {
+                return Err(syn::Error::new_spanned(
+                    p,
+                    "actor RPC arguments must be plain identifiers",
+                ))
+            }
+        };
+        if let syn::Type::Reference(r) = &*pat_type.ty {
+            return Err(syn::Error::new_spanned(
+                r,
+                "actor RPC arguments must be owned types",
+            ));
+        }
+        arg_names.push(name);
+        arg_types.push((*pat_type.ty).clone());
+    }
+
+    if let syn::ReturnType::Default = sig.output {
+        return Err(syn::Error::new(
+            sig.span(),
+            "actor RPC operations must return a `HandlerResult`",
+        ));
+    }
+
+    let name = to_pascal_case(&sig.ident.to_string());
+    Ok(Operation {
+        method: sig.ident.clone(),
+        args_struct: syn::Ident::new(&format!("{}Args", name), Span::call_site()),
+        name,
+        arg_names,
+        arg_types,
+        output: sig.output.clone(),
+    })
+}
+
+/// Converts a method name into the operation name used on the wire, e.g. `get_user` becomes
+/// `GetUser`, matching the naming of the operations in the WIDL schemas
+pub(crate) fn to_pascal_case(s: &str) -> String {
+    s.split('_')
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(c) => c.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn to_snake_case(s: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in s.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
@@ -0,0 +1,7 @@
+fn main() {
+    wasmcloud_widl_codegen::Generator::new("core.widl")
+        .handler_role("Actor")
+        .core_crate("crate")
+        .build()
+        .unwrap();
+}
@@ -0,0 +1,456 @@
+//! # Payload codecs
+//!
+//! Every payload exchanged between an actor and the host is encoded with one of the formats in
+//! [Format](enum.Format.html). The [serialize](fn.serialize.html) and [deserialize](fn.deserialize.html)
+//! functions in this module back the `serialize` and `deserialize` functions of every wasmCloud
+//! interface crate.
+//!
+//! ## Format tags
+//! Payloads in any format other than msgpack with struct maps (the original wasmCloud wire format)
+//! start with a two-byte tag: the byte `0xc1`, which msgpack reserves and never emits and which
+//! cannot start a UTF-8 string, followed by the [code](enum.Format.html#method.code) of the format.
+//! Untagged payloads are always msgpack, so actors and providers that predate format tags keep
+//! working unchanged.
+//!
+//! ## Negotiation
+//! Incoming payloads are decoded according to their tag, so an actor can always read whatever
+//! format the host or a provider chose to send. Outgoing payloads use the format set with
+//! [set_format](fn.set_format.html), which defaults to [Format::MsgPack](enum.Format.html#variant.MsgPack).
+//! Since every payload names its format, the host learns which formats an actor speaks from the
+//! payloads it sends. Only opt into another format when the host and the providers the actor
+//! talks to understand format tags.
+//!
+//! CBOR support requires the `cbor` feature.
+//!
+//! # Example
+//! ```
+//! use wasmcloud_actor_core::codec::{self, Codec, Format, MsgPackCompact};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Reading {
+//!     sensor: String,
+//!     value: f64,
+//! }
+//!
+//! let reading = Reading { sensor: "temp".to_string(), value: 21.5 };
+//! let legacy = codec::encode(Format::MsgPack, &reading).unwrap();
+//! let compact = codec::encode(Format::MsgPackCompact, &reading).unwrap();
+//! assert!(compact.len() < legacy.len());
+//!
+//! // Incoming payloads are decoded according to their tag
+//! assert_eq!(codec::decode::<Reading>(&compact).unwrap(), reading);
+//! assert_eq!(codec::detect(&compact).unwrap().0, Format::MsgPackCompact);
+//!
+//! // Individual codecs can also be used directly, without a tag
+//! let bytes = MsgPackCompact.encode(&reading).unwrap();
+//! assert_eq!(MsgPackCompact.decode::<Reading>(&bytes).unwrap(), reading);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::io::Cursor;
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// The first byte of a tagged payload. `0xc1` is never used by msgpack and is not a valid
+/// first byte of a UTF-8 string
+const TAG: u8 = 0xc1;
+
+thread_local! {
+    static FORMAT: Cell<Format> = const { Cell::new(Format::MsgPack) };
+}
+
+/// Encodes and decodes payloads in a single format
+pub trait Codec {
+    /// The format produced by this codec
+    fn format(&self) -> Format;
+
+    /// Encodes an item, without a format tag
+    fn encode<T: Serialize + ?Sized>(&self, item: &T) -> Result<Vec<u8>, Error>;
+
+    /// Decodes an item from an untagged payload
+    fn decode<'de, T: Deserialize<'de>>(&self, buf: &[u8]) -> Result<T, Error>;
+
+    /// Decodes an item from an untagged payload, borrowing strings and byte arrays from the
+    /// payload instead of copying them where the format allows
+    fn decode_borrowed<'de, T: Deserialize<'de>>(&self, buf: &'de [u8]) -> Result<T, Error>;
+}
+
+/// The payload formats understood by wasmCloud actors
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Format {
+    /// msgpack with structs encoded as maps keyed by field name. This is the original wasmCloud
+    /// wire format and is never tagged
+    MsgPack,
+    /// msgpack with structs encoded as arrays of field values, which omits field names from
+    /// the payload
+    MsgPackCompact,
+    /// JSON
+    Json,
+    /// CBOR (requires the `cbor` feature)
+    Cbor,
+}
+
+impl Format {
+    /// The byte identifying this format in a payload tag
+    pub fn code(&self) -> u8 {
+        match self {
+            Format::MsgPack => 0,
+            Format::MsgPackCompact => 1,
+            Format::Json => 2,
+            Format::Cbor => 3,
+        }
+    }
+
+    /// Returns the format identified by the given tag byte, if it is known
+    pub fn from_code(code: u8) -> Option<Format> {
+        match code {
+            0 => Some(Format::MsgPack),
+            1 => Some(Format::MsgPackCompact),
+            2 => Some(Format::Json),
+            3 => Some(Format::Cbor),
+            _ => None,
+        }
+    }
+}
+
+impl Codec for Format {
+    fn format(&self) -> Format {
+        *self
+    }
+
+    fn encode<T: Serialize + ?Sized>(&self, item: &T) -> Result<Vec<u8>, Error> {
+        match self {
+            Format::MsgPack => MsgPack.encode(item),
+            Format::MsgPackCompact => MsgPackCompact.encode(item),
+            Format::Json => Json.encode(item),
+            Format::Cbor => Cbor.encode(item),
+        }
+    }
+
+    fn decode<'de, T: Deserialize<'de>>(&self, buf: &[u8]) -> Result<T, Error> {
+        match self {
+            Format::MsgPack => MsgPack.decode(buf),
+            Format::MsgPackCompact => MsgPackCompact.decode(buf),
+            Format::Json => Json.decode(buf),
+            Format::Cbor => Cbor.decode(buf),
+        }
+    }
+
+    fn decode_borrowed<'de, T: Deserialize<'de>>(&self, buf: &'de [u8]) -> Result<T, Error> {
+        match self {
+            Format::MsgPack => MsgPack.decode_borrowed(buf),
+            Format::MsgPackCompact => MsgPackCompact.decode_borrowed(buf),
+            Format::Json => Json.decode_borrowed(buf),
+            Format::Cbor => Cbor.decode_borrowed(buf),
+        }
+    }
+}
+
+/// msgpack with structs encoded as maps
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MsgPack;
+
+impl Codec for MsgPack {
+    fn format(&self) -> Format {
+        Format::MsgPack
+    }
+
+    fn encode<T: Serialize + ?Sized>(&self, item: &T) -> Result<Vec<u8>, Error> {
+        let mut buf = Vec::new();
+        item.serialize(&mut rmp_serde::Serializer::new(&mut buf).with_struct_map())?;
+        Ok(buf)
+    }
+
+    fn decode<'de, T: Deserialize<'de>>(&self, buf: &[u8]) -> Result<T, Error> {
+        decode_msgpack(buf)
+    }
+
+    fn decode_borrowed<'de, T: Deserialize<'de>>(&self, buf: &'de [u8]) -> Result<T, Error> {
+        decode_msgpack_borrowed(buf)
+    }
+}
+
+/// msgpack with structs encoded as arrays
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MsgPackCompact;
+
+impl Codec for MsgPackCompact {
+    fn format(&self) -> Format {
+        Format::MsgPackCompact
+    }
+
+    fn encode<T: Serialize + ?Sized>(&self, item: &T) -> Result<Vec<u8>, Error> {
+        let mut buf = Vec::new();
+        item.serialize(&mut rmp_serde::Serializer::new(&mut buf))?;
+        Ok(buf)
+    }
+
+    fn decode<'de, T: Deserialize<'de>>(&self, buf: &[u8]) -> Result<T, Error> {
+        decode_msgpack(buf)
+    }
+
+    fn decode_borrowed<'de, T: Deserialize<'de>>(&self, buf: &'de [u8]) -> Result<T, Error> {
+        decode_msgpack_borrowed(buf)
+    }
+}
+
+/// JSON
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Json;
+
+impl Codec for Json {
+    fn format(&self) -> Format {
+        Format::Json
+    }
+
+    fn encode<T: Serialize + ?Sized>(&self, item: &T) -> Result<Vec<u8>, Error> {
+        Ok(serde_json::to_vec(item)?)
+    }
+
+    fn decode<'de, T: Deserialize<'de>>(&self, buf: &[u8]) -> Result<T, Error> {
+        let mut de = serde_json::Deserializer::from_reader(Cursor::new(buf));
+        match Deserialize::deserialize(&mut de) {
+            Ok(t) => Ok(t),
+            Err(e) => Err(format!("Failed to de-serialize: {}", e).into()),
+        }
+    }
+
+    /// JSON cannot represent byte arrays as contiguous bytes, so items that borrow byte arrays
+    /// cannot be decoded from JSON payloads
+    fn decode_borrowed<'de, T: Deserialize<'de>>(&self, buf: &'de [u8]) -> Result<T, Error> {
+        match serde_json::from_slice(buf) {
+            Ok(t) => Ok(t),
+            Err(e) => Err(format!("Failed to de-serialize: {}", e).into()),
+        }
+    }
+}
+
+/// CBOR. Encoding and decoding fail unless the `cbor` feature is enabled
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Cbor;
+
+impl Codec for Cbor {
+    fn format(&self) -> Format {
+        Format::Cbor
+    }
+
+    #[cfg(feature = "cbor")]
+    fn encode<T: Serialize + ?Sized>(&self, item: &T) -> Result<Vec<u8>, Error> {
+        Ok(serde_cbor::to_vec(&item)?)
+    }
+
+    #[cfg(not(feature = "cbor"))]
+    fn encode<T: Serialize + ?Sized>(&self, _item: &T) -> Result<Vec<u8>, Error> {
+        Err(CBOR_DISABLED.into())
+    }
+
+    #[cfg(feature = "cbor")]
+    fn decode<'de, T: Deserialize<'de>>(&self, buf: &[u8]) -> Result<T, Error> {
+        let mut de = serde_cbor::Deserializer::from_reader(Cursor::new(buf));
+        match Deserialize::deserialize(&mut de) {
+            Ok(t) => Ok(t),
+            Err(e) => Err(format!("Failed to de-serialize: {}", e).into()),
+        }
+    }
+
+    #[cfg(not(feature = "cbor"))]
+    fn decode<'de, T: Deserialize<'de>>(&self, _buf: &[u8]) -> Result<T, Error> {
+        Err(CBOR_DISABLED.into())
+    }
+
+    #[cfg(feature = "cbor")]
+    fn decode_borrowed<'de, T: Deserialize<'de>>(&self, buf: &'de [u8]) -> Result<T, Error> {
+        match serde_cbor::from_slice(buf) {
+            Ok(t) => Ok(t),
+            Err(e) => Err(format!("Failed to de-serialize: {}", e).into()),
+        }
+    }
+
+    #[cfg(not(feature = "cbor"))]
+    fn decode_borrowed<'de, T: Deserialize<'de>>(&self, _buf: &'de [u8]) -> Result<T, Error> {
+        Err(CBOR_DISABLED.into())
+    }
+}
+
+#[cfg(not(feature = "cbor"))]
+const CBOR_DISABLED: &str = "CBOR payloads require the `cbor` feature of wasmcloud-actor-core";
+
+fn decode_msgpack<'de, T: Deserialize<'de>>(buf: &[u8]) -> Result<T, Error> {
+    let mut de = rmp_serde::Deserializer::new(Cursor::new(buf));
+    match Deserialize::deserialize(&mut de) {
+        Ok(t) => Ok(t),
+        Err(e) => Err(format!("Failed to de-serialize: {}", e).into()),
+    }
+}
+
+fn decode_msgpack_borrowed<'de, T: Deserialize<'de>>(buf: &'de [u8]) -> Result<T, Error> {
+    match rmp_serde::from_read_ref(buf) {
+        Ok(t) => Ok(t),
+        Err(e) => Err(format!("Failed to de-serialize: {}", e).into()),
+    }
+}
+
+/// Sets the format of all payloads subsequently sent by the actor. The format is kept per
+/// thread, so tests running in parallel can use different formats
+pub fn set_format(format: Format) {
+    FORMAT.with(|f| f.set(format));
+}
+
+/// The format of all payloads sent by the actor
+pub fn format() -> Format {
+    FORMAT.with(|f| f.get())
+}
+
+/// Encodes an item in the given format, prefixed with a format tag unless the format is
+/// [Format::MsgPack](enum.Format.html#variant.MsgPack)
+pub fn encode<T: Serialize + ?Sized>(format: Format, item: &T) -> Result<Vec<u8>, Error> {
+    if format == Format::MsgPack {
+        return MsgPack.encode(item);
+    }
+    let mut buf = vec![TAG, format.code()];
+    buf.extend(format.encode(item)?);
+    Ok(buf)
+}
+
+/// Decodes a payload in the format named by its tag. Untagged payloads are decoded as msgpack
+pub fn decode<'de, T: Deserialize<'de>>(buf: &[u8]) -> Result<T, Error> {
+    match detect(buf) {
+        Some((format, body)) => format.decode(body),
+        None => Err(unknown_format(buf)),
+    }
+}
+
+/// Decodes a payload in the format named by its tag, borrowing strings and byte arrays from
+/// the payload where the format allows. Untagged payloads are decoded as msgpack
+pub fn decode_borrowed<'de, T: Deserialize<'de>>(buf: &'de [u8]) -> Result<T, Error> {
+    match detect(buf) {
+        Some((format, body)) => format.decode_borrowed(body),
+        None => Err(unknown_format(buf)),
+    }
+}
+
+/// Returns the format of a payload along with its body, stripped of any format tag, or `None`
+/// if the payload is tagged with an unknown format. Untagged payloads are msgpack
+pub fn detect(buf: &[u8]) -> Option<(Format, &[u8])> {
+    match buf {
+        [TAG, code, body @ ..] => Format::from_code(*code).map(|format| (format, body)),
+        _ => Some((Format::MsgPack, buf)),
+    }
+}
+
+fn unknown_format(buf: &[u8]) -> Error {
+    format!("Failed to de-serialize: unknown payload format {}", buf[1]).into()
+}
+
+/// Serializes an item in the format set with [set_format](fn.set_format.html)
+pub fn serialize<T: Serialize>(item: T) -> Result<Vec<u8>, Error> {
+    encode(format(), &item)
+}
+
+/// Deserializes a payload in the format named by its tag
+pub fn deserialize<'de, T: Deserialize<'de>>(buf: &[u8]) -> Result<T, Error> {
+    decode(buf)
+}
+
+/// Deserializes a payload in the format named by its tag, borrowing strings and byte arrays
+/// from the payload where the format allows
+pub fn deserialize_borrowed<'de, T: Deserialize<'de>>(buf: &'de [u8]) -> Result<T, Error> {
+    decode_borrowed(buf)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Message {
+        subject: String,
+        body: Vec<u8>,
+        headers: HashMap<String, String>,
+    }
+
+    fn message() -> Message {
+        let mut headers = HashMap::new();
+        headers.insert("traceparent".to_string(), "00-01".to_string());
+        Message {
+            subject: "updates".to_string(),
+            body: b"hello".to_vec(),
+            headers,
+        }
+    }
+
+    #[test]
+    fn round_trips_every_format() {
+        let mut formats = vec![Format::MsgPack, Format::MsgPackCompact, Format::Json];
+        if cfg!(feature = "cbor") {
+            formats.push(Format::Cbor);
+        }
+        for format in formats {
+            let buf = encode(format, &message()).unwrap();
+            assert_eq!(detect(&buf).unwrap().0, format);
+            assert_eq!(decode::<Message>(&buf).unwrap(), message(), "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_formats() {
+        let buf = [TAG, 0x7f, 0x90];
+        assert_eq!(detect(&buf), None);
+        assert_eq!(
+            decode::<Vec<u8>>(&buf).unwrap_err().to_string(),
+            "Failed to de-serialize: unknown payload format 127"
+        );
+        assert!(decode_borrowed::<Vec<u8>>(&buf).is_err());
+        assert_eq!(detect(&[0x90]), Some((Format::MsgPack, &[0x90][..])));
+    }
+
+    #[test]
+    fn msgpack_is_untagged() {
+        let buf = encode(Format::MsgPack, &message()).unwrap();
+        assert_eq!(buf, MsgPack.encode(&message()).unwrap());
+        // Compact payloads can be read by peers that do not understand format tags
+        let compact = MsgPackCompact.encode(&message()).unwrap();
+        assert_eq!(MsgPack.decode::<Message>(&compact).unwrap(), message());
+    }
+
+    #[test]
+    fn serializes_in_selected_format() {
+        set_format(Format::Json);
+        let buf = serialize(message()).unwrap();
+        assert_eq!(&buf[..2], &[TAG, Format::Json.code()]);
+        assert_eq!(deserialize::<Message>(&buf).unwrap(), message());
+        set_format(Format::MsgPack);
+    }
+
+    #[test]
+    fn borrows_from_payload() {
+        #[derive(Serialize, Deserialize)]
+        struct Chunk<'a> {
+            id: &'a str,
+            #[serde(with = "serde_bytes")]
+            bytes: &'a [u8],
+        }
+
+        let chunk = Chunk {
+            id: "poem",
+            bytes: b"verse",
+        };
+        for format in [Format::MsgPack, Format::MsgPackCompact] {
+            let buf = encode(format, &chunk).unwrap();
+            let decoded: Chunk = decode_borrowed(&buf).unwrap();
+            assert_eq!(decoded.id, "poem");
+            assert_eq!(decoded.bytes, b"verse");
+            let start = buf.as_ptr() as usize;
+            let offset = decoded.bytes.as_ptr() as usize - start;
+            assert!(offset < buf.len());
+        }
+    }
+
+    #[test]
+    fn unknown_tag_is_an_error() {
+        assert!(decode::<Message>(&[TAG, 42, 0x80]).is_err());
+    }
+}
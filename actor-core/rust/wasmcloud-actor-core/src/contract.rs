@@ -0,0 +1,318 @@
+//! # Contract descriptors
+//!
+//! Every interface crate exports a static `CONTRACT` describing the contract it implements: the
+//! capability id, the roles with their operations, the argument and return types of each
+//! operation, the types declared in the schema and the version of the crate. Descriptors are
+//! generated from the same WIDL schema as the bindings, so they always match them.
+//!
+//! Descriptors can be serialized (e.g. to JSON) for tooling, and
+//! [Contract::type_json_schema](struct.Contract.html#method.type_json_schema),
+//! [Contract::args_json_schema](struct.Contract.html#method.args_json_schema) and
+//! [Contract::returns_json_schema](struct.Contract.html#method.returns_json_schema) produce JSON
+//! Schemas for the payloads exchanged by an operation. Byte arrays, which msgpack encodes as
+//! binary, are described as arrays of bytes.
+//!
+//! # Example
+//! ```
+//! use wasmcloud_actor_core::contract::{Direction, Type};
+//! use wasmcloud_actor_core::CONTRACT;
+//!
+//! assert_eq!(CONTRACT.capability_id, "core");
+//!
+//! let (role, op) = CONTRACT.operation("HealthRequest").unwrap();
+//! assert_eq!(role.direction, Direction::ToActor);
+//! assert_eq!(op.returns, Type::Named("HealthCheckResponse"));
+//!
+//! let schema = CONTRACT.returns_json_schema("HealthRequest").unwrap();
+//! assert_eq!(schema["$ref"], "#/definitions/HealthCheckResponse");
+//! assert_eq!(
+//!     schema["definitions"]["HealthCheckResponse"]["properties"]["healthy"]["type"],
+//!     "boolean"
+//! );
+//! ```
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// The contract implemented by an interface crate
+#[derive(Debug, PartialEq, Serialize, Clone, Copy)]
+pub struct Contract {
+    /// The capability id, e.g. `wasmcloud:keyvalue`
+    pub capability_id: &'static str,
+    /// The version of the crate that exports the descriptor
+    pub version: &'static str,
+    pub roles: &'static [Role],
+    /// Every type declared in the schema
+    pub types: &'static [TypeSchema],
+}
+
+/// Which side of the contract invokes the operations of a role
+#[derive(Debug, PartialEq, Eq, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// Operations that actors invoke on the capability provider through `Host`
+    ToProvider,
+    /// Operations that the host delivers to actors through `Handlers`
+    ToActor,
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone, Copy)]
+pub struct Role {
+    pub name: &'static str,
+    pub description: Option<&'static str>,
+    pub direction: Direction,
+    pub operations: &'static [Operation],
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone, Copy)]
+pub struct Operation {
+    pub name: &'static str,
+    pub description: Option<&'static str>,
+    /// Whether the single argument is sent as is instead of being wrapped in an arguments map
+    pub unary: bool,
+    pub args: &'static [Field],
+    pub returns: Type,
+}
+
+/// A type declared in the schema
+#[derive(Debug, PartialEq, Serialize, Clone, Copy)]
+pub struct TypeSchema {
+    pub name: &'static str,
+    pub description: Option<&'static str>,
+    pub fields: &'static [Field],
+}
+
+/// A field of a type or an argument of an operation, as named on the wire
+#[derive(Debug, PartialEq, Serialize, Clone, Copy)]
+pub struct Field {
+    pub name: &'static str,
+    pub description: Option<&'static str>,
+    #[serde(rename = "type")]
+    pub ty: Type,
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Type {
+    /// A scalar (`bool`, `i8`-`i64`, `u8`-`u64`, `f32`, `f64`, `string` or `bytes`) or a type
+    /// declared in the schema
+    Named(&'static str),
+    List(&'static Type),
+    /// A map with keys of the first type and values of the second
+    Map(&'static Type, &'static Type),
+    Optional(&'static Type),
+    /// No value, only used for the return type of operations
+    Void,
+}
+
+impl Contract {
+    /// Finds an operation by name, along with the role that declares it
+    pub fn operation(&self, name: &str) -> Option<(&'static Role, &'static Operation)> {
+        self.roles.iter().find_map(|role| {
+            role.operations
+                .iter()
+                .find(|op| op.name == name)
+                .map(|op| (role, op))
+        })
+    }
+
+    pub fn type_schema(&self, name: &str) -> Option<&'static TypeSchema> {
+        self.types.iter().find(|t| t.name == name)
+    }
+
+    /// The JSON Schema of a type declared in the schema
+    pub fn type_json_schema(&self, name: &str) -> Option<Value> {
+        let ty = self.type_schema(name)?;
+        Some(self.json_schema(Type::Named(ty.name)))
+    }
+
+    /// The JSON Schema of the payload sent to an operation
+    pub fn args_json_schema(&self, operation: &str) -> Option<Value> {
+        let (_, op) = self.operation(operation)?;
+        match op.args {
+            [arg] if op.unary => Some(self.json_schema(arg.ty)),
+            args => {
+                let mut refs = Vec::new();
+                let mut schema = object_schema(args, &mut refs);
+                self.add_definitions(&mut schema, refs);
+                Some(schema)
+            }
+        }
+    }
+
+    /// The JSON Schema of the reply of an operation
+    pub fn returns_json_schema(&self, operation: &str) -> Option<Value> {
+        let (_, op) = self.operation(operation)?;
+        Some(self.json_schema(op.returns))
+    }
+
+    fn json_schema(&self, ty: Type) -> Value {
+        let mut refs = Vec::new();
+        let mut schema = type_schema(&ty, &mut refs);
+        self.add_definitions(&mut schema, refs);
+        schema
+    }
+
+    /// Adds the schemas of the given declared types, and of every declared type they refer to
+    fn add_definitions(&self, schema: &mut Value, mut pending: Vec<&'static str>) {
+        let mut definitions = Map::new();
+        while let Some(name) = pending.pop() {
+            if definitions.contains_key(name) {
+                continue;
+            }
+            if let Some(ty) = self.type_schema(name) {
+                let mut def = object_schema(ty.fields, &mut pending);
+                if let Some(description) = ty.description {
+                    def["description"] = json!(description);
+                }
+                definitions.insert(name.to_string(), def);
+            }
+        }
+        schema["$schema"] = json!("http://json-schema.org/draft-07/schema#");
+        if !definitions.is_empty() {
+            schema["definitions"] = Value::Object(definitions);
+        }
+    }
+}
+
+/// The JSON Schema of an object with the given fields. Optional fields are not required
+fn object_schema(fields: &[Field], refs: &mut Vec<&'static str>) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for field in fields {
+        let mut schema = type_schema(&field.ty, refs);
+        if let Some(description) = field.description {
+            schema["description"] = json!(description);
+        }
+        properties.insert(field.name.to_string(), schema);
+        if !matches!(field.ty, Type::Optional(_)) {
+            required.push(field.name);
+        }
+    }
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// The JSON Schema of a type, recording the declared types it refers to
+fn type_schema(ty: &Type, refs: &mut Vec<&'static str>) -> Value {
+    match ty {
+        Type::Named(name) => match *name {
+            "bool" => json!({ "type": "boolean" }),
+            "i8" | "i16" | "i32" | "i64" => json!({ "type": "integer" }),
+            "u8" | "u16" | "u32" | "u64" => json!({ "type": "integer", "minimum": 0 }),
+            "f32" | "f64" => json!({ "type": "number" }),
+            "string" => json!({ "type": "string" }),
+            "bytes" => json!({
+                "type": "array",
+                "items": { "type": "integer", "minimum": 0, "maximum": 255 }
+            }),
+            name => {
+                refs.push(name);
+                json!({ "$ref": format!("#/definitions/{}", name) })
+            }
+        },
+        Type::List(item) => json!({ "type": "array", "items": type_schema(item, refs) }),
+        Type::Map(_, value) => json!({
+            "type": "object",
+            "additionalProperties": type_schema(value, refs)
+        }),
+        Type::Optional(inner) => json!({ "anyOf": [type_schema(inner, refs), { "type": "null" }] }),
+        Type::Void => json!({ "type": "null" }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static EXAMPLE: Contract = Contract {
+        capability_id: "wasmcloud:example",
+        version: "0.1.0",
+        roles: &[Role {
+            name: "Store",
+            description: None,
+            direction: Direction::ToProvider,
+            operations: &[Operation {
+                name: "Get",
+                description: Some("Gets a value"),
+                unary: false,
+                args: &[
+                    Field {
+                        name: "key",
+                        description: None,
+                        ty: Type::Named("string"),
+                    },
+                    Field {
+                        name: "limit",
+                        description: None,
+                        ty: Type::Optional(&Type::Named("u32")),
+                    },
+                ],
+                returns: Type::Named("GetResponse"),
+            }],
+        }],
+        types: &[
+            TypeSchema {
+                name: "GetResponse",
+                description: Some("Response type for Get operations"),
+                fields: &[Field {
+                    name: "items",
+                    description: None,
+                    ty: Type::List(&Type::Named("Item")),
+                }],
+            },
+            TypeSchema {
+                name: "Item",
+                description: None,
+                fields: &[Field {
+                    name: "values",
+                    description: None,
+                    ty: Type::Map(&Type::Named("string"), &Type::Named("bytes")),
+                }],
+            },
+        ],
+    };
+
+    #[test]
+    fn describes_args() {
+        let schema = EXAMPLE.args_json_schema("Get").unwrap();
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], json!(["key"]));
+        assert_eq!(schema["properties"]["limit"]["anyOf"][1]["type"], "null");
+        assert!(schema.get("definitions").is_none());
+    }
+
+    #[test]
+    fn describes_nested_types() {
+        let schema = EXAMPLE.returns_json_schema("Get").unwrap();
+        assert_eq!(schema["$ref"], "#/definitions/GetResponse");
+        let definitions = &schema["definitions"];
+        assert_eq!(
+            definitions["GetResponse"]["properties"]["items"]["items"]["$ref"],
+            "#/definitions/Item"
+        );
+        assert_eq!(
+            definitions["Item"]["properties"]["values"]["additionalProperties"]["type"],
+            "array"
+        );
+        assert_eq!(
+            definitions["GetResponse"]["description"],
+            "Response type for Get operations"
+        );
+        assert!(EXAMPLE.type_json_schema("Missing").is_none());
+        assert!(EXAMPLE.args_json_schema("Missing").is_none());
+    }
+
+    #[test]
+    fn serializes_descriptors() {
+        let json = serde_json::to_value(EXAMPLE).unwrap();
+        assert_eq!(json["roles"][0]["direction"], "to_provider");
+        assert_eq!(
+            json["roles"][0]["operations"][0]["args"][1]["type"],
+            json!({ "optional": { "named": "u32" } })
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! # Structured errors
+//!
+//! [ActorError](struct.ActorError.html) is the error type shared by all wasmCloud interface
+//! crates. It carries an [ErrorKind](enum.ErrorKind.html) that callers can match on instead of
+//! parsing error strings.
+//!
+//! An `ActorError` can cross the wire in two ways. Inside a payload it is serialized with the
+//! standard msgpack [serialize](../fn.serialize.html) function like any other codec struct.
+//! Where only a string can be carried, such as the `error` field of a provider's result type or
+//! the error returned by a handler, it is rendered as `<kind>: <message>` (e.g.
+//! `not_found: no such container`), and [ActorError::parse](struct.ActorError.html#method.parse)
+//! recovers the kind on the other side. Strings that do not start with a known kind are treated
+//! as `Internal` errors, so errors from providers that predate this type still convert cleanly.
+//!
+//! # Example
+//! ```
+//! use wasmcloud_actor_core::{ActorError, ErrorKind};
+//!
+//! let err = ActorError::not_found("no such container");
+//! assert_eq!(err.to_string(), "not_found: no such container");
+//!
+//! let parsed = ActorError::parse(&err.to_string());
+//! assert_eq!(parsed.kind(), ErrorKind::NotFound);
+//! assert_eq!(parsed.message(), "no such container");
+//!
+//! assert_eq!(ActorError::parse("disk on fire").kind(), ErrorKind::Internal);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The category of an [ActorError](struct.ActorError.html)
+#[derive(Debug, PartialEq, Eq, Hash, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// The requested entity (key, container, object, actor, ...) does not exist
+    NotFound,
+    /// The request was malformed or contained an invalid value
+    InvalidArgument,
+    /// The capability provider or one of its dependencies is currently unavailable
+    Unavailable,
+    /// The operation did not complete in time
+    Timeout,
+    /// The caller is not allowed to perform the operation
+    PermissionDenied,
+    /// Any other failure
+    Internal,
+}
+
+impl ErrorKind {
+    const ALL: [ErrorKind; 6] = [
+        ErrorKind::NotFound,
+        ErrorKind::InvalidArgument,
+        ErrorKind::Unavailable,
+        ErrorKind::Timeout,
+        ErrorKind::PermissionDenied,
+        ErrorKind::Internal,
+    ];
+
+    /// The code used for this kind on the wire, e.g. `not_found`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::NotFound => "not_found",
+            ErrorKind::InvalidArgument => "invalid_argument",
+            ErrorKind::Unavailable => "unavailable",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::PermissionDenied => "permission_denied",
+            ErrorKind::Internal => "internal",
+        }
+    }
+
+    /// Whether an operation that failed with this kind of error may succeed if retried
+    pub fn is_transient(&self) -> bool {
+        matches!(self, ErrorKind::Unavailable | ErrorKind::Timeout)
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An error with a kind that can be matched on, shared by all interface crates
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct ActorError {
+    #[serde(rename = "kind")]
+    kind: ErrorKind,
+    #[serde(rename = "message")]
+    message: String,
+}
+
+impl ActorError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> ActorError {
+        ActorError {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> ActorError {
+        ActorError::new(ErrorKind::NotFound, message)
+    }
+
+    pub fn invalid_argument(message: impl Into<String>) -> ActorError {
+        ActorError::new(ErrorKind::InvalidArgument, message)
+    }
+
+    pub fn unavailable(message: impl Into<String>) -> ActorError {
+        ActorError::new(ErrorKind::Unavailable, message)
+    }
+
+    pub fn timeout(message: impl Into<String>) -> ActorError {
+        ActorError::new(ErrorKind::Timeout, message)
+    }
+
+    pub fn permission_denied(message: impl Into<String>) -> ActorError {
+        ActorError::new(ErrorKind::PermissionDenied, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> ActorError {
+        ActorError::new(ErrorKind::Internal, message)
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Recovers an error from its string form, `<kind>: <message>`. A string without a known
+    /// kind becomes an `Internal` error carrying the entire string as its message
+    pub fn parse(s: &str) -> ActorError {
+        ErrorKind::ALL
+            .iter()
+            .find_map(|kind| {
+                s.strip_prefix(kind.as_str())
+                    .and_then(|rest| rest.strip_prefix(": "))
+                    .map(|message| ActorError::new(*kind, message))
+            })
+            .unwrap_or_else(|| ActorError::internal(s))
+    }
+}
+
+impl fmt::Display for ActorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind, self.message)
+    }
+}
+
+impl std::error::Error for ActorError {}
+
+/// Conversion of an interface crate's result type, such as `BlobstoreResult`, into a `Result`
+/// with a structured error. It is also implemented for a `HandlerResult` of such a type, so the
+/// return value of a `Host` method can be converted in a single step, e.g.
+/// `blobstore::default().remove_container(id).into_result()`
+pub trait IntoActorResult {
+    type Ok;
+
+    fn into_result(self) -> Result<Self::Ok, ActorError>;
+}
+
+impl<T: IntoActorResult> IntoActorResult for Result<T, Box<dyn std::error::Error + Send + Sync>> {
+    type Ok = T::Ok;
+
+    fn into_result(self) -> Result<T::Ok, ActorError> {
+        self.map_err(ActorError::from)?.into_result()
+    }
+}
+
+/// Converts the error of a `HandlerResult`, such as one returned by a `Host` method. An
+/// `ActorError` is passed through unchanged; any other error is parsed from its string form
+impl From<Box<dyn std::error::Error + Send + Sync>> for ActorError {
+    fn from(e: Box<dyn std::error::Error + Send + Sync>) -> ActorError {
+        match e.downcast::<ActorError>() {
+            Ok(e) => *e,
+            Err(e) => ActorError::parse(&e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{deserialize, serialize};
+
+    #[test]
+    fn round_trips_through_msgpack() {
+        let err = ActorError::permission_denied("missing claim");
+        let bytes = serialize(&err).unwrap();
+        assert_eq!(deserialize::<ActorError>(&bytes).unwrap(), err);
+    }
+
+    #[test]
+    fn round_trips_through_strings() {
+        for kind in ErrorKind::ALL.iter() {
+            let err = ActorError::new(*kind, "it: broke");
+            assert_eq!(ActorError::parse(&err.to_string()), err);
+        }
+        assert_eq!(ActorError::parse("timeouts: 3").kind(), ErrorKind::Internal);
+        assert_eq!(ActorError::parse("timeouts: 3").message(), "timeouts: 3");
+    }
+
+    #[test]
+    fn converts_boxed_errors() {
+        let boxed: Box<dyn std::error::Error + Send + Sync> = ActorError::timeout("slow").into();
+        assert_eq!(ActorError::from(boxed), ActorError::timeout("slow"));
+
+        let boxed: Box<dyn std::error::Error + Send + Sync> = "unavailable: down".into();
+        assert_eq!(ActorError::from(boxed), ActorError::unavailable("down"));
+    }
+
+    #[test]
+    fn flattens_handler_results() {
+        struct Ack(Option<String>);
+        impl IntoActorResult for Ack {
+            type Ok = ();
+            fn into_result(self) -> Result<(), ActorError> {
+                self.0.map_or(Ok(()), |e| Err(ActorError::parse(&e)))
+            }
+        }
+
+        let ok: Result<Ack, Box<dyn std::error::Error + Send + Sync>> = Ok(Ack(None));
+        assert_eq!(ok.into_result(), Ok(()));
+        let nack: Result<Ack, Box<dyn std::error::Error + Send + Sync>> =
+            Ok(Ack(Some("not_found: gone".to_string())));
+        assert_eq!(nack.into_result(), Err(ActorError::not_found("gone")));
+        let failed: Result<Ack, Box<dyn std::error::Error + Send + Sync>> =
+            Err("link not found".into());
+        assert_eq!(
+            failed.into_result().unwrap_err().kind(),
+            ErrorKind::Internal
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! # Async handlers
+//!
+//! With the `async` feature enabled, actors can be written as idiomatic async Rust. Because actors
+//! are single-threaded and the host answers every call synchronously, this module only contains a
+//! minimal executor: [block_on](fn.block_on.html) drives a future to completion on the current
+//! thread, and [join](fn.join.html), [join3](fn.join3.html) and [join_all](fn.join_all.html) combine
+//! several futures, such as calls to different capability providers.
+//!
+//! `async fn` handlers can be registered with the `handler` attribute, and the `init` function may
+//! itself be `async`:
+//!
+//! ```
+//! extern crate wasmcloud_actor_core as actor;
+//! use actor::{HealthCheckRequest, HealthCheckResponse};
+//! use wapc_guest::HandlerResult;
+//!
+//! #[actor::handler(actor::HealthRequest)]
+//! async fn health(_req: HealthCheckRequest) -> HandlerResult<HealthCheckResponse> {
+//!     let message: String = actor::call_actor_async("echo", "Echo", &"ok".to_string()).await?;
+//!     Ok(HealthCheckResponse { healthy: true, message })
+//! }
+//!
+//! #[actor::init]
+//! async fn init() {}
+//! # fn main() {
+//! #     wapc_init();
+//! #     actor::testing::expect("default", "echo", "Echo", |msg: String| Ok(msg));
+//! #     let res: HealthCheckResponse =
+//! #         actor::testing::invoke("HealthRequest", HealthCheckRequest::default()).unwrap();
+//! #     assert_eq!(res.message, "ok");
+//! # }
+//! ```
+//!
+//! Handlers can also be registered by hand with a closure that blocks on the async function:
+//!
+//! ```ignore
+//! http::Handlers::register_handle_request(|req| executor::block_on(handle_request(req)));
+//! ```
+//!
+//! Any synchronous `Host` call can be awaited by wrapping it in an `async` block, and raw host
+//! calls and actor-to-actor calls are available as [host_call_async](../fn.host_call_async.html)
+//! and [call_actor_async](../fn.call_actor_async.html). Calls are made when their future is first
+//! polled, in the order in which they are polled.
+//!
+//! # Example
+//! ```
+//! use wasmcloud_actor_core::executor::{block_on, join};
+//! use wapc_guest::HandlerResult;
+//!
+//! async fn get(key: &str) -> HandlerResult<String> {
+//!     // e.g. keyvalue::default().get(key.to_string())
+//!     Ok(key.to_uppercase())
+//! }
+//!
+//! async fn combined() -> HandlerResult<String> {
+//!     let (a, b) = join(get("a"), async { get("b").await }).await;
+//!     Ok(format!("{}{}", a?, b?))
+//! }
+//!
+//! assert_eq!(block_on(combined()).unwrap(), "AB");
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+struct Signal(AtomicBool);
+
+impl Wake for Signal {
+    fn wake(self: Arc<Self>) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Runs a future to completion on the current thread and returns its output.
+///
+/// # Panics
+/// Panics if the future returns `Poll::Pending` without waking itself. An actor has no other
+/// thread that could complete such a future, so waiting for it would block the actor forever.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let signal = Arc::new(Signal(AtomicBool::new(false)));
+    let waker = Waker::from(signal.clone());
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        if !signal.0.swap(false, Ordering::SeqCst) {
+            panic!("future is pending, but nothing can wake it within a single-threaded actor");
+        }
+    }
+}
+
+enum MaybeDone<F: Future> {
+    Pending(Pin<Box<F>>),
+    Done(F::Output),
+    Taken,
+}
+
+impl<F: Future> MaybeDone<F> {
+    fn new(future: F) -> Self {
+        MaybeDone::Pending(Box::pin(future))
+    }
+
+    /// Polls the future if it has not completed yet, returning whether it has completed
+    fn poll(&mut self, cx: &mut Context<'_>) -> bool {
+        if let MaybeDone::Pending(future) = self {
+            match future.as_mut().poll(cx) {
+                Poll::Ready(output) => *self = MaybeDone::Done(output),
+                Poll::Pending => return false,
+            }
+        }
+        true
+    }
+
+    fn take(&mut self) -> F::Output {
+        match std::mem::replace(self, MaybeDone::Taken) {
+            MaybeDone::Done(output) => output,
+            _ => unreachable!("output taken before the future completed"),
+        }
+    }
+}
+
+// Every future is stored behind a `Pin<Box<_>>`, so `MaybeDone` itself never needs to be pinned
+impl<F: Future> Unpin for MaybeDone<F> {}
+
+/// Waits for two futures to complete, polling them in order, and returns both outputs
+pub async fn join<A, B>(a: A, b: B) -> (A::Output, B::Output)
+where
+    A: Future,
+    B: Future,
+{
+    let (mut a, mut b) = (MaybeDone::new(a), MaybeDone::new(b));
+    std::future::poll_fn(|cx| {
+        let done = a.poll(cx) & b.poll(cx);
+        if done {
+            Poll::Ready((a.take(), b.take()))
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+/// Waits for three futures to complete, polling them in order, and returns all outputs
+pub async fn join3<A, B, C>(a: A, b: B, c: C) -> (A::Output, B::Output, C::Output)
+where
+    A: Future,
+    B: Future,
+    C: Future,
+{
+    let ((a, b), c) = join(join(a, b), c).await;
+    (a, b, c)
+}
+
+/// Waits for every future to complete, polling them in order, and returns their outputs in
+/// the same order
+pub async fn join_all<I>(futures: I) -> Vec<<I::Item as Future>::Output>
+where
+    I: IntoIterator,
+    I::Item: Future,
+{
+    let mut futures: Vec<_> = futures.into_iter().map(MaybeDone::new).collect();
+    std::future::poll_fn(|cx| {
+        let done = futures.iter_mut().fold(true, |done, f| f.poll(cx) & done);
+        if done {
+            Poll::Ready(futures.iter_mut().map(MaybeDone::take).collect())
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// A future that returns `Pending` a given number of times before completing
+    struct Yield(u32);
+
+    impl Future for Yield {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 == 0 {
+                return Poll::Ready(());
+            }
+            self.0 -= 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn polls_futures_in_order() {
+        let log = RefCell::new(Vec::new());
+        let step = |name: &'static str, yields: u32| {
+            let log = &log;
+            async move {
+                Yield(yields).await;
+                log.borrow_mut().push(name);
+                name
+            }
+        };
+
+        let out = block_on(join_all(vec![step("a", 2), step("b", 0), step("c", 1)]));
+        assert_eq!(out, vec!["a", "b", "c"]);
+        assert_eq!(*log.borrow(), vec!["b", "c", "a"]);
+
+        let out = block_on(join3(step("x", 0), async { 1 }, Yield(3)));
+        assert_eq!(out, ("x", 1, ()));
+    }
+
+    #[test]
+    #[should_panic(expected = "nothing can wake it")]
+    fn stalled_future_panics() {
+        block_on(std::future::pending::<()>());
+    }
+}
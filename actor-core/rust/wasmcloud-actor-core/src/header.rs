@@ -0,0 +1,343 @@
+//! # Header maps
+//!
+//! [HeaderMap](struct.HeaderMap.html) holds the headers of the requests and responses of the HTTP
+//! interfaces. Header names are compared case-insensitively, and a header can have several
+//! values, as `Set-Cookie` or `Accept` often do.
+//!
+//! On the wire, the HTTP contracts carry headers in two fields, so that actors and capability
+//! providers built before multiple values were supported keep working:
+//!
+//! * `header`, a map from each name to its values joined with `, `, which is all older peers read
+//!   and send. `Set-Cookie` values can contain commas, as in `Expires=Thu, 01 Jan 1970`, so only
+//!   the first one is kept there
+//! * `headerValues`, an optional map from each name to the list of its values, which takes
+//!   precedence over `header` when it is present
+//!
+//! The [fields](fields/index.html) module (de)serializes a `HeaderMap` to and from these fields.
+//!
+//! # Example
+//! ```
+//! use wasmcloud_actor_core::header::HeaderMap;
+//!
+//! let mut headers = HeaderMap::new();
+//! headers.insert("Content-Type", "text/html");
+//! headers.append("Set-Cookie", "a=1");
+//! headers.append("set-cookie", "b=2");
+//! headers.append("Vary", "Accept");
+//! headers.append("Vary", "Origin");
+//!
+//! assert_eq!(headers.get("content-type"), Some("text/html"));
+//! assert_eq!(headers.get_all("SET-COOKIE"), ["a=1", "b=2"]);
+//! assert_eq!(headers.to_joined()["Vary"], "Accept, Origin");
+//! assert_eq!(headers.to_joined()["Set-Cookie"], "a=1");
+//! ```
+
+use std::collections::HashMap;
+use std::iter::FromIterator;
+use std::ops::Index;
+
+/// A multimap of header names to values, with case-insensitive names. Names keep the case in
+/// which they were first added, and values keep the order in which they were added
+#[derive(Debug, Default, Clone)]
+pub struct HeaderMap {
+    entries: Vec<(String, String)>,
+}
+
+impl HeaderMap {
+    pub fn new() -> Self {
+        HeaderMap::default()
+    }
+
+    /// The first value of a header
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Every value of a header, in the order they were added
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Sets the value of a header, replacing any values it had
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let name = name.into();
+        match self
+            .entries
+            .iter()
+            .position(|(n, _)| n.eq_ignore_ascii_case(&name))
+        {
+            Some(i) => {
+                let name = self.entries[i].0.clone();
+                self.entries.retain(|(n, _)| !n.eq_ignore_ascii_case(&name));
+                self.entries.insert(i, (name, value.into()));
+            }
+            None => self.entries.push((name, value.into())),
+        }
+    }
+
+    /// Adds a value to a header, keeping the values it already had
+    pub fn append(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let name = name.into();
+        let name = match self
+            .entries
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(&name))
+        {
+            Some((existing, _)) => existing.clone(),
+            None => name,
+        };
+        self.entries.push((name, value.into()));
+    }
+
+    /// Removes a header, returning its values
+    pub fn remove(&mut self, name: &str) -> Vec<String> {
+        let (removed, kept) = std::mem::take(&mut self.entries)
+            .into_iter()
+            .partition(|(n, _)| n.eq_ignore_ascii_case(name));
+        self.entries = kept;
+        removed.into_iter().map(|(_, v)| v).collect()
+    }
+
+    /// The number of values, counting every value of headers with several
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The names and values of the headers, with one item per value
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+
+    /// Each header with its values joined with `, `, as carried by the `header` field of the HTTP
+    /// contracts. `Set-Cookie` keeps only its first value, since joining cookies whose attributes
+    /// contain commas would corrupt them
+    pub fn to_joined(&self) -> HashMap<String, String> {
+        self.to_lists()
+            .into_iter()
+            .map(|(n, mut values)| {
+                let joined = if n.eq_ignore_ascii_case("Set-Cookie") {
+                    values.swap_remove(0)
+                } else {
+                    values.join(", ")
+                };
+                (n, joined)
+            })
+            .collect()
+    }
+
+    /// Each header with the list of its values, as carried by the `headerValues` field of the
+    /// HTTP contracts
+    pub fn to_lists(&self) -> HashMap<String, Vec<String>> {
+        let mut lists: HashMap<String, Vec<String>> = HashMap::new();
+        for (name, value) in self.entries.iter() {
+            lists.entry(name.clone()).or_default().push(value.clone());
+        }
+        lists
+    }
+
+    /// Builds a header map from the `header` and `headerValues` fields of the HTTP contracts.
+    /// `headerValues` is used if it is present, since `header` may have joined values
+    pub fn from_fields(
+        header: HashMap<String, String>,
+        header_values: Option<HashMap<String, Vec<String>>>,
+    ) -> HeaderMap {
+        match header_values {
+            Some(lists) => lists
+                .into_iter()
+                .flat_map(|(name, values)| values.into_iter().map(move |v| (name.clone(), v)))
+                .collect(),
+            None => header.into_iter().collect(),
+        }
+    }
+}
+
+/// Header maps are equal when they have the same values for every header, regardless of the
+/// case of the names and of the order of different headers
+impl PartialEq for HeaderMap {
+    fn eq(&self, other: &HeaderMap) -> bool {
+        self.len() == other.len()
+            && self
+                .entries
+                .iter()
+                .all(|(name, _)| self.get_all(name) == other.get_all(name))
+    }
+}
+
+/// Returns the first value of a header, as indexing the `HashMap` that header maps replace did
+///
+/// # Panics
+/// If the header is not present
+impl Index<&str> for HeaderMap {
+    type Output = String;
+
+    fn index(&self, name: &str) -> &String {
+        self.entries
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v)
+            .unwrap_or_else(|| panic!("no header named `{}`", name))
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for HeaderMap {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut headers = HeaderMap::new();
+        headers.extend(iter);
+        headers
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> Extend<(K, V)> for HeaderMap {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (name, value) in iter {
+            self.append(name, value);
+        }
+    }
+}
+
+impl From<HashMap<String, String>> for HeaderMap {
+    fn from(map: HashMap<String, String>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl IntoIterator for HeaderMap {
+    type Item = (String, String);
+    type IntoIter = std::vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+/// (De)serializes a [HeaderMap](../struct.HeaderMap.html) to and from the `header` and
+/// `headerValues` fields of the struct that contains it. Use it on a flattened field:
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use wasmcloud_actor_core::header::HeaderMap;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Response {
+///     #[serde(flatten, with = "wasmcloud_actor_core::header::fields")]
+///     header: HeaderMap,
+/// }
+/// ```
+pub mod fields {
+    use super::HeaderMap;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    #[derive(Serialize)]
+    struct Fields {
+        header: HashMap<String, String>,
+        #[serde(rename = "headerValues")]
+        header_values: HashMap<String, Vec<String>>,
+    }
+
+    #[derive(Deserialize)]
+    struct OptionalFields {
+        #[serde(default)]
+        header: HashMap<String, String>,
+        #[serde(rename = "headerValues", default)]
+        header_values: Option<HashMap<String, Vec<String>>>,
+    }
+
+    pub fn serialize<S: Serializer>(headers: &HeaderMap, serializer: S) -> Result<S::Ok, S::Error> {
+        Fields {
+            header: headers.to_joined(),
+            header_values: headers.to_lists(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HeaderMap, D::Error> {
+        let fields = OptionalFields::deserialize(deserializer)?;
+        Ok(HeaderMap::from_fields(fields.header, fields.header_values))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Message {
+        #[serde(flatten, with = "fields")]
+        header: HeaderMap,
+        #[serde(with = "serde_bytes")]
+        body: Vec<u8>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct LegacyMessage {
+        header: HashMap<String, String>,
+        #[serde(with = "serde_bytes")]
+        body: Vec<u8>,
+    }
+
+    #[test]
+    fn edits_headers() {
+        let mut headers: HeaderMap = vec![("Accept", "text/html"), ("X-Id", "1")]
+            .into_iter()
+            .collect();
+        headers.append("accept", "application/json");
+        assert_eq!(headers.get_all("ACCEPT"), ["text/html", "application/json"]);
+        assert_eq!(headers["x-id"], "1");
+
+        headers.insert("x-ID", "2");
+        assert_eq!(headers.get_all("X-Id"), ["2"]);
+        assert_eq!(headers.iter().nth(1), Some(("X-Id", "2")));
+
+        assert_eq!(headers.remove("Accept").len(), 2);
+        assert_eq!(headers.len(), 1);
+        assert!(!headers.contains_key("accept"));
+    }
+
+    #[test]
+    fn keeps_the_wire_format_compatible() {
+        let mut header = HeaderMap::new();
+        header.append("Set-Cookie", "a=1; Expires=Thu, 01 Jan 1970 00:00:00 GMT");
+        header.append("Set-Cookie", "b=2");
+        header.append("Accept", "text/plain");
+        header.append("Accept", "text/html");
+        header.insert("Content-Type", "text/plain");
+        let message = Message {
+            header,
+            body: b"hi".to_vec(),
+        };
+
+        let payload = crate::serialize(&message).unwrap();
+        let decoded: Message = crate::deserialize(&payload).unwrap();
+        assert_eq!(decoded, message);
+
+        let legacy: LegacyMessage = crate::deserialize(&payload).unwrap();
+        assert_eq!(
+            legacy.header["Set-Cookie"],
+            "a=1; Expires=Thu, 01 Jan 1970 00:00:00 GMT"
+        );
+        assert_eq!(legacy.header["Accept"], "text/plain, text/html");
+        assert_eq!(legacy.body, b"hi");
+
+        let payload = crate::serialize(&legacy).unwrap();
+        let decoded: Message = crate::deserialize(&payload).unwrap();
+        assert_eq!(decoded.header.get_all("accept"), ["text/plain, text/html"]);
+        assert_eq!(decoded.header.get("content-type"), Some("text/plain"));
+    }
+}
@@ -0,0 +1,249 @@
+//! # Composable health checks
+//!
+//! Rather than replacing the default health check handler, modules within an actor can register
+//! named probes, such as "keyvalue reachable" or "config loaded". The health check handler
+//! generated by the `init` macro runs every registered probe and aggregates the results into a
+//! single [HealthCheckResponse](../struct.HealthCheckResponse.html):
+//!
+//! * `healthy` is `true` only if every _critical_ probe succeeded. Probes registered as
+//!   non-critical are reported, but never make the actor unhealthy.
+//! * `message` contains a JSON-encoded [HealthReport](struct.HealthReport.html) with a
+//!   per-probe breakdown. It is left empty if no probes are registered.
+//!
+//! Probes run synchronously, one after another, so a probe cannot be interrupted once it has
+//! started. A probe with a timeout is instead marked as failed if it takes longer than its
+//! timeout to complete. Elapsed time is measured with `std::time::Instant` where the target
+//! supports it; on `wasm32` targets a time source must be provided with
+//! [set_clock](fn.set_clock.html), otherwise timeouts are not enforced.
+//!
+//! # Example
+//! ```
+//! use wasmcloud_actor_core::health::{self, Probe};
+//! use std::time::Duration;
+//!
+//! health::register("config loaded", || Ok(()));
+//! health::register_probe(
+//!     Probe::new("cache warm", || Err("cache is cold".into()))
+//!         .non_critical()
+//!         .with_timeout(Duration::from_millis(50)),
+//! );
+//!
+//! let res = health::check();
+//! assert!(res.healthy);
+//! let report = health::HealthReport::from_response(&res).unwrap();
+//! assert!(!report.probes[1].healthy);
+//! ```
+
+use crate::HealthCheckResponse;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// The result of a single probe. Returning `Err` marks the probe as failed, with the error's
+/// message included in the health report.
+pub type ProbeResult = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+type ProbeFn = Box<dyn Fn() -> ProbeResult + Send + Sync>;
+
+lazy_static::lazy_static! {
+    static ref PROBES: RwLock<Vec<Probe>> = RwLock::new(Vec::new());
+    static ref CLOCK: RwLock<Option<fn() -> Duration>> = RwLock::new(None);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+lazy_static::lazy_static! {
+    static ref START: std::time::Instant = std::time::Instant::now();
+}
+
+/// A named check of one of the actor's dependencies
+pub struct Probe {
+    name: String,
+    critical: bool,
+    timeout: Option<Duration>,
+    check: ProbeFn,
+}
+
+impl Probe {
+    /// Creates a critical probe without a timeout
+    pub fn new<F>(name: &str, check: F) -> Probe
+    where
+        F: Fn() -> ProbeResult + Send + Sync + 'static,
+    {
+        Probe {
+            name: name.to_string(),
+            critical: true,
+            timeout: None,
+            check: Box::new(check),
+        }
+    }
+
+    /// Marks the probe as non-critical. A failing non-critical probe is reported, but does
+    /// not make the actor unhealthy
+    pub fn non_critical(mut self) -> Probe {
+        self.critical = false;
+        self
+    }
+
+    /// Marks the probe as failed if it takes longer than `timeout` to complete
+    pub fn with_timeout(mut self, timeout: Duration) -> Probe {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn run(&self) -> ProbeReport {
+        let started = now();
+        let result = (self.check)();
+        let elapsed = match (started, now()) {
+            (Some(start), Some(end)) => Some(end.checked_sub(start).unwrap_or_default()),
+            _ => None,
+        };
+
+        let (healthy, message) = match (result, self.timeout, elapsed) {
+            (Err(e), _, _) => (false, e.to_string()),
+            (Ok(()), Some(timeout), Some(elapsed)) if elapsed > timeout => {
+                (false, format!("timed out after {}ms", timeout.as_millis()))
+            }
+            (Ok(()), _, _) => (true, String::new()),
+        };
+        ProbeReport {
+            name: self.name.clone(),
+            healthy,
+            critical: self.critical,
+            message,
+            elapsed_ms: elapsed.map(|e| e.as_millis() as u64),
+        }
+    }
+}
+
+/// The outcome of a single probe, as reported in a [HealthReport](struct.HealthReport.html)
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct ProbeReport {
+    /// The name the probe was registered with
+    pub name: String,
+    /// Whether the probe succeeded within its timeout
+    pub healthy: bool,
+    /// Whether a failure of this probe makes the actor unhealthy
+    pub critical: bool,
+    /// The error message of a failed probe, empty otherwise
+    pub message: String,
+    /// How long the probe took to run, if a time source is available
+    #[serde(rename = "elapsedMs")]
+    pub elapsed_ms: Option<u64>,
+}
+
+/// The per-probe breakdown carried in the `message` of an aggregated health check response
+#[derive(Debug, PartialEq, Deserialize, Serialize, Default, Clone)]
+pub struct HealthReport {
+    /// The outcome of every registered probe, in registration order
+    pub probes: Vec<ProbeReport>,
+}
+
+impl HealthReport {
+    /// Parses the report out of a health check response produced by [check](fn.check.html).
+    /// Returns `None` if the message does not contain a report
+    pub fn from_response(response: &HealthCheckResponse) -> Option<HealthReport> {
+        serde_json::from_str(&response.message).ok()
+    }
+}
+
+/// Registers a critical probe without a timeout. Registering a probe with the name of an
+/// existing probe replaces it
+pub fn register<F>(name: &str, check: F)
+where
+    F: Fn() -> ProbeResult + Send + Sync + 'static,
+{
+    register_probe(Probe::new(name, check));
+}
+
+/// Registers a probe. Registering a probe with the name of an existing probe replaces it
+pub fn register_probe(probe: Probe) {
+    let mut probes = PROBES.write().unwrap();
+    match probes.iter_mut().find(|p| p.name == probe.name) {
+        Some(existing) => *existing = probe,
+        None => probes.push(probe),
+    }
+}
+
+/// Removes the probe with the given name, if one is registered
+pub fn unregister(name: &str) {
+    PROBES.write().unwrap().retain(|p| p.name != name);
+}
+
+/// Sets the time source used to enforce probe timeouts. The function must return a
+/// monotonically increasing duration since an arbitrary, fixed point in time
+pub fn set_clock(clock: fn() -> Duration) {
+    *CLOCK.write().unwrap() = Some(clock);
+}
+
+/// Runs every registered probe, in registration order, and aggregates the results. Probes
+/// must not register or remove probes themselves
+pub fn check() -> HealthCheckResponse {
+    aggregate(&PROBES.read().unwrap())
+}
+
+fn aggregate(probes: &[Probe]) -> HealthCheckResponse {
+    if probes.is_empty() {
+        return HealthCheckResponse::healthy();
+    }
+    let report = HealthReport {
+        probes: probes.iter().map(Probe::run).collect(),
+    };
+    HealthCheckResponse {
+        healthy: report.probes.iter().all(|p| p.healthy || !p.critical),
+        message: serde_json::to_string(&report).unwrap_or_default(),
+    }
+}
+
+fn now() -> Option<Duration> {
+    if let Some(clock) = *CLOCK.read().unwrap() {
+        return Some(clock());
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    return Some(START.elapsed());
+    #[cfg(target_arch = "wasm32")]
+    return None;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn no_probes_is_healthy() {
+        assert_eq!(aggregate(&[]), HealthCheckResponse::healthy());
+    }
+
+    #[test]
+    fn critical_failure_is_unhealthy() {
+        let res = aggregate(&[
+            Probe::new("ok", || Ok(())),
+            Probe::new("broken", || Err("connection refused".into())),
+        ]);
+        assert!(!res.healthy);
+
+        let report = HealthReport::from_response(&res).unwrap();
+        assert_eq!(report.probes.len(), 2);
+        assert!(report.probes[0].healthy);
+        assert_eq!(report.probes[1].name, "broken");
+        assert_eq!(report.probes[1].message, "connection refused");
+    }
+
+    #[test]
+    fn non_critical_failure_is_healthy() {
+        let res = aggregate(&[Probe::new("broken", || Err("nope".into())).non_critical()]);
+        assert!(res.healthy);
+        assert!(!HealthReport::from_response(&res).unwrap().probes[0].healthy);
+    }
+
+    #[test]
+    fn slow_probe_times_out() {
+        let res = aggregate(&[Probe::new("slow", || {
+            std::thread::sleep(Duration::from_millis(20));
+            Ok(())
+        })
+        .with_timeout(Duration::from_millis(1))]);
+        assert!(!res.healthy);
+        let report = HealthReport::from_response(&res).unwrap();
+        assert_eq!(report.probes[0].message, "timed out after 1ms");
+    }
+}
@@ -0,0 +1,208 @@
+//! # Interceptors
+//!
+//! Interceptors wrap every invocation that the host delivers to the actor, before it reaches
+//! the handler registered for its operation, and every call that the actor makes to the host
+//! through [host_call](../fn.host_call.html). Since the `Handlers` and `Host` types of all
+//! wasmCloud interface crates go through this crate, an interceptor registered once in `init`
+//! applies to all of them. Typical uses are timing, logging, authorization checks and mapping
+//! errors.
+//!
+//! An interceptor receives the call along with the rest of the chain as `next`. It can inspect
+//! or replace the payload before calling `next`, inspect or replace the result afterwards, or
+//! not call `next` at all to reject the call. Interceptors run in the order in which they were
+//! registered, so the first one registered is the outermost.
+//!
+//! Host calls made by an interceptor go through the chain again, so an interceptor that logs
+//! host calls with the logging interface should skip calls made to `wasmcloud:logging`.
+//!
+//! # Example
+//! ```
+//! use wasmcloud_actor_core::interceptor::{self, HostCall, Interceptor, Invocation};
+//! use wapc_guest::CallResult;
+//!
+//! struct Auth;
+//!
+//! impl Interceptor for Auth {
+//!     fn invocation(&self, inv: &Invocation, next: &dyn Fn(&Invocation) -> CallResult) -> CallResult {
+//!         if inv.operation == "Shutdown" {
+//!             return Err("permission denied: Shutdown is not allowed".into());
+//!         }
+//!         next(inv)
+//!     }
+//!
+//!     fn host_call(&self, call: &HostCall, next: &dyn Fn(&HostCall) -> CallResult) -> CallResult {
+//!         next(call).map_err(|e| format!("{} failed: {}", call.operation, e).into())
+//!     }
+//! }
+//!
+//! interceptor::register(Auth);
+//! let res = interceptor::invocation("Shutdown", &[], |_payload| Ok(vec![]));
+//! assert!(res.is_err());
+//! ```
+
+use std::sync::{Arc, RwLock};
+use wapc_guest::CallResult;
+
+lazy_static::lazy_static! {
+    static ref INTERCEPTORS: RwLock<Vec<Arc<dyn Interceptor>>> = RwLock::new(Vec::new());
+}
+
+/// An invocation delivered by the host to the actor
+#[derive(Debug, Clone, Copy)]
+pub struct Invocation<'a> {
+    /// The name of the operation, e.g. `HandleRequest`
+    pub operation: &'a str,
+    /// The serialized arguments of the invocation
+    pub payload: &'a [u8],
+}
+
+/// A call made by the actor to the host
+#[derive(Debug, Clone, Copy)]
+pub struct HostCall<'a> {
+    /// The link name the call is made on, e.g. `default`
+    pub binding: &'a str,
+    /// The namespace (capability contract ID) of the call, e.g. `wasmcloud:keyvalue`
+    pub namespace: &'a str,
+    /// The name of the operation, e.g. `Get`
+    pub operation: &'a str,
+    /// The serialized arguments of the call
+    pub payload: &'a [u8],
+}
+
+/// Runs around inbound invocations and outbound host calls. Both methods pass the call on
+/// unchanged by default, so an interceptor only needs to implement the ones it is interested in
+pub trait Interceptor: Send + Sync {
+    /// Runs around the handler of an invocation delivered by the host
+    fn invocation(&self, inv: &Invocation, next: &dyn Fn(&Invocation) -> CallResult) -> CallResult {
+        next(inv)
+    }
+
+    /// Runs around a call made to the host
+    fn host_call(&self, call: &HostCall, next: &dyn Fn(&HostCall) -> CallResult) -> CallResult {
+        next(call)
+    }
+}
+
+/// Adds an interceptor to the end of the chain. Interceptors are usually registered in `init`
+pub fn register(interceptor: impl Interceptor + 'static) {
+    INTERCEPTORS.write().unwrap().push(Arc::new(interceptor));
+}
+
+/// The registered interceptors. The list is copied so that interceptors can register others
+/// without deadlocking
+fn chain() -> Vec<Arc<dyn Interceptor>> {
+    INTERCEPTORS.read().unwrap().clone()
+}
+
+/// Runs an invocation through the interceptor chain, ending with `handler`, in a new
+/// [trace context](../trace/index.html). The `Handlers` of the interface crates call this from
+/// the functions they register with the host
+pub fn invocation<F>(operation: &str, payload: &[u8], handler: F) -> CallResult
+where
+    F: Fn(&[u8]) -> CallResult,
+{
+    fn run(
+        chain: &[Arc<dyn Interceptor>],
+        inv: &Invocation,
+        handler: &dyn Fn(&[u8]) -> CallResult,
+    ) -> CallResult {
+        match chain.split_first() {
+            Some((first, rest)) => first.invocation(inv, &|inv| run(rest, inv, handler)),
+            None => handler(inv.payload),
+        }
+    }
+    let inv = Invocation { operation, payload };
+    crate::trace::invocation(payload, || run(&chain(), &inv, &handler))
+}
+
+/// Runs a host call through the interceptor chain, ending with `call`
+pub(crate) fn host_call<F>(binding: &str, ns: &str, op: &str, msg: &[u8], call: F) -> CallResult
+where
+    F: Fn(&HostCall) -> CallResult,
+{
+    fn run(
+        chain: &[Arc<dyn Interceptor>],
+        hc: &HostCall,
+        call: &dyn Fn(&HostCall) -> CallResult,
+    ) -> CallResult {
+        match chain.split_first() {
+            Some((first, rest)) => first.host_call(hc, &|hc| run(rest, hc, call)),
+            None => call(hc),
+        }
+    }
+    let hc = HostCall {
+        binding,
+        namespace: ns,
+        operation: op,
+        payload: msg,
+    };
+    run(&chain(), &hc, &call)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{host_call, serialize, testing};
+
+    /// Only intercepts calls in the `intercepted` namespace and `Intercepted*` operations, as
+    /// interceptors are global and tests run in parallel
+    struct Uppercase;
+
+    impl Interceptor for Uppercase {
+        fn invocation(
+            &self,
+            inv: &Invocation,
+            next: &dyn Fn(&Invocation) -> CallResult,
+        ) -> CallResult {
+            if !inv.operation.starts_with("Intercepted") {
+                return next(inv);
+            }
+            let payload = inv.payload.to_ascii_uppercase();
+            let res = next(&Invocation {
+                payload: &payload,
+                ..*inv
+            })?;
+            Ok([&res[..], b"!"].concat())
+        }
+
+        fn host_call(&self, call: &HostCall, next: &dyn Fn(&HostCall) -> CallResult) -> CallResult {
+            if call.namespace != "intercepted" {
+                return next(call);
+            }
+            next(call).map_err(|e| format!("internal: {}", e).into())
+        }
+    }
+
+    struct Reject;
+
+    impl Interceptor for Reject {
+        fn invocation(
+            &self,
+            inv: &Invocation,
+            next: &dyn Fn(&Invocation) -> CallResult,
+        ) -> CallResult {
+            if inv.operation == "InterceptedRejected" {
+                Err("permission denied".into())
+            } else {
+                next(inv)
+            }
+        }
+    }
+
+    #[test]
+    fn runs_around_calls() {
+        register(Uppercase);
+        register(Reject);
+
+        let res = invocation("InterceptedEcho", b"hello", |payload| Ok(payload.to_vec()));
+        assert_eq!(res.unwrap(), b"HELLO!");
+        let res = invocation("InterceptedRejected", b"hello", |_| unreachable!());
+        assert_eq!(res.unwrap_err().to_string(), "permission denied");
+        let res = invocation("Echo", b"hello", |payload| Ok(payload.to_vec()));
+        assert_eq!(res.unwrap(), b"hello");
+
+        testing::expect_raw("default", "intercepted", "Get", |_| Err("gone".into()));
+        let err = host_call("default", "intercepted", "Get", &serialize("key").unwrap());
+        assert_eq!(err.unwrap_err().to_string(), "internal: gone");
+    }
+}
@@ -6,8 +6,9 @@
 //! by capability providers to receive link data for an actor.
 //!
 //! If you use the `init` macro, then a default health check handler will be created for you, as shown in
-//! this example. If you want to provide your own custom health check handler, then simply call
-//! `Handlers::register_health_check` with your handler function.
+//! this example. The default handler aggregates any probes registered with the [health](health/index.html)
+//! module, and reports the actor as healthy if none are registered. If you want to provide your own custom
+//! health check handler, then simply call `Handlers::register_health_check` with your handler function.
 //!
 //! # Example
 //! ```
@@ -25,10 +26,134 @@
 //! function cannot communicate with capability providers.
 //!
 //! Also, in keeping with the notion of _stateless_ actors, avoid using this function to initialize or create global state.
+//!
+//! # Payload formats
+//! Payloads are encoded as msgpack by default. An actor can opt into compact msgpack, JSON or CBOR with
+//! [codec::set_format](codec/fn.set_format.html); every payload in a format other than the default carries a
+//! small tag naming its format, so incoming payloads are always decoded correctly.
+//!
+//! # Errors
+//! [ActorError](struct.ActorError.html) is the structured error type shared by all wasmCloud interface crates.
+//! Its [ErrorKind](enum.ErrorKind.html) survives the trip through the host, so callers can match on it rather
+//! than parsing error strings. See the [error](error/index.html) module for how it is carried on the wire.
+//!
+//! # Link configuration
+//! Capability providers can parse the values of a `CapabilityConfiguration` into a typed struct by deriving
+//! `FromLinkValues` (requires the `derive` feature, which is enabled by `guest`). See the [link](link/index.html)
+//! module for the supported field types and attributes.
+//!
+//! # Declarative handlers
+//! Instead of registering handlers by hand inside `init`, a function can be marked as the handler for an
+//! interface operation with the `handler` attribute. Every such function is registered by the `wapc_init` that
+//! `init` generates, wherever it is declared in the actor.
+//!
+//! ```
+//! extern crate wasmcloud_actor_core as actor;
+//! use actor::{HealthCheckRequest, HealthCheckResponse};
+//! use wapc_guest::HandlerResult;
+//!
+//! #[actor::handler(actor::HealthRequest)]
+//! fn health(_req: HealthCheckRequest) -> HandlerResult<HealthCheckResponse> {
+//!     Ok(HealthCheckResponse {
+//!         healthy: false,
+//!         message: "not ready".to_string(),
+//!     })
+//! }
+//!
+//! #[actor::init]
+//! fn init() {}
+//! # fn main() {
+//! #     wapc_init();
+//! #     let res: HealthCheckResponse =
+//! #         actor::testing::invoke("HealthRequest", HealthCheckRequest::default()).unwrap();
+//! #     assert!(!res.healthy);
+//! # }
+//! ```
+//!
+//! # Interceptors
+//! Interceptors registered with [interceptor::register](interceptor/fn.register.html) run around every invocation
+//! delivered to the actor's handlers and every call it makes to the host, across all interface crates. See the
+//! [interceptor](interceptor/index.html) module.
+//!
+//! # Tracing
+//! Each invocation runs with a W3C-compatible trace context, which the interface crates extract from inbound
+//! requests and messages and attach to outbound calls. See the [trace](trace/index.html) module.
+//!
+//! # Headers
+//! The [header](header/index.html) module contains the case-insensitive, multi-value `HeaderMap` used by the HTTP
+//! interfaces, along with the backward-compatible way in which it is carried on the wire.
+//!
+//! # Interface contracts
+//! Every interface crate, this one included, exports a static `CONTRACT` that describes its capability id, roles,
+//! operations and types, along with the version of the crate. See the [contract](contract/index.html) module for
+//! the descriptor types and for generating JSON Schemas of the payloads.
+//!
+//! # Async handlers
+//! With the `async` feature, handlers and the `init` function can be `async fn`s, and calls to the host can be
+//! awaited. The [executor](executor/index.html) module contains the single-threaded executor that drives them.
+//!
+//! # Actor-to-actor contracts
+//! The `rpc` attribute turns a trait into a typed contract between actors. The calling actor uses the generated
+//! client, while the called actor registers an implementation of the trait with the generated handlers.
+//!
+//! ```
+//! extern crate wasmcloud_actor_core as actor;
+//! use wapc_guest::HandlerResult;
+//!
+//! #[actor::rpc]
+//! pub trait Greeter {
+//!     fn greet(&self, name: String) -> HandlerResult<String>;
+//! }
+//!
+//! #[derive(Default)]
+//! struct MyGreeter;
+//!
+//! impl Greeter for MyGreeter {
+//!     fn greet(&self, name: String) -> HandlerResult<String> {
+//!         Ok(format!("Hello, {}!", name))
+//!     }
+//! }
+//!
+//! #[actor::init]
+//! fn init() {
+//!     GreeterHandlers::register::<MyGreeter>();
+//! }
+//!
+//! fn call_greeter() -> HandlerResult<String> {
+//!     GreeterClient::new("greeter").greet("world".to_string())
+//! }
+//! # fn main() {
+//! #     wapc_init();
+//! #     actor::testing::expect_raw("default", "greeter", "Greet", |payload| {
+//! #         actor::testing::invoke_raw("Greet", payload)
+//! #     });
+//! #     assert_eq!(call_greeter().unwrap(), "Hello, world!");
+//! # }
+//! ```
 
-mod generated;
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+}
 pub use generated::*;
 
+pub mod codec;
+
+pub mod contract;
+pub mod error;
+#[cfg(feature = "async")]
+pub mod executor;
+pub mod header;
+pub mod health;
+#[cfg(feature = "guest")]
+pub mod interceptor;
+pub mod link;
+pub mod trace;
+
+pub use error::{ActorError, ErrorKind, IntoActorResult};
+
+#[cfg(all(feature = "guest", not(target_arch = "wasm32")))]
+pub mod testing;
+
 #[cfg(feature = "guest")]
 use serde::{Deserialize, Serialize};
 
@@ -41,11 +166,56 @@ pub fn call_actor<'de, T: Serialize, U: Deserialize<'de>>(
     operation: &str,
     msg: &T,
 ) -> wapc_guest::HandlerResult<U> {
-    let res = wapc_guest::host_call("default", actor_ref, operation, &generated::serialize(msg)?)?;
+    let res = host_call("default", actor_ref, operation, &generated::serialize(msg)?)?;
     let res = generated::deserialize(&res)?;
     Ok(res)
 }
 
+#[cfg(feature = "guest")]
+/// Performs a call to the host on the given binding. All `Host` methods in the wasmCloud
+/// interface crates go through this function, and through the registered [interceptors](interceptor/index.html).
+/// When the actor is compiled for a target other than `wasm32`, the call is answered by the in-process mock
+/// host in the [testing](testing/index.html) module instead of a waPC host.
+pub fn host_call(binding: &str, ns: &str, op: &str, msg: &[u8]) -> wapc_guest::CallResult {
+    interceptor::host_call(binding, ns, op, msg, |call| {
+        #[cfg(target_arch = "wasm32")]
+        return wapc_guest::host_call(call.binding, call.namespace, call.operation, call.payload);
+        #[cfg(not(target_arch = "wasm32"))]
+        return testing::host_call(call.binding, call.namespace, call.operation, call.payload);
+    })
+}
+
+#[cfg(feature = "async")]
+/// Performs an actor-to-actor call when awaited. See [call_actor](fn.call_actor.html)
+pub async fn call_actor_async<T: Serialize, U: serde::de::DeserializeOwned>(
+    actor_ref: &str,
+    operation: &str,
+    msg: &T,
+) -> wapc_guest::HandlerResult<U> {
+    call_actor(actor_ref, operation, msg)
+}
+
+#[cfg(feature = "async")]
+/// Performs a call to the host when awaited. See [host_call](fn.host_call.html)
+pub async fn host_call_async(
+    binding: &str,
+    ns: &str,
+    op: &str,
+    msg: &[u8],
+) -> wapc_guest::CallResult {
+    host_call(binding, ns, op, msg)
+}
+
+#[cfg(feature = "guest")]
+/// Registers a function to handle the given operation when it is invoked by the host. All
+/// `Handlers` in the wasmCloud interface crates register through this function, which also
+/// makes them available to [testing::invoke](testing/fn.invoke.html) on non-`wasm32` targets.
+pub fn register_function(name: &str, f: fn(&[u8]) -> wapc_guest::CallResult) {
+    #[cfg(not(target_arch = "wasm32"))]
+    testing::register_function(name, f);
+    wapc_guest::register_function(name, f);
+}
+
 impl HealthCheckResponse {
     pub fn healthy() -> HealthCheckResponse {
         HealthCheckResponse {
@@ -56,4 +226,43 @@ impl HealthCheckResponse {
 }
 
 #[cfg(feature = "guest")]
-pub use wasmcloud_actor_core_derive::init;
+pub use wasmcloud_actor_core_derive::{handler, init, rpc};
+
+pub use link::{FromLinkValue, FromLinkValues, LinkValuesError};
+#[cfg(feature = "derive")]
+pub use wasmcloud_actor_core_derive::FromLinkValues;
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "guest")]
+    pub use inventory;
+    pub use serde;
+    #[cfg(feature = "guest")]
+    pub use wapc_guest;
+
+    /// The registration function that `#[handler]` generates for a handler, submitted to the
+    /// inventory so that the `wapc_init` generated by `#[init]` can call every one of them
+    #[cfg(feature = "guest")]
+    pub struct Registration(pub fn());
+
+    #[cfg(feature = "guest")]
+    inventory::collect!(Registration);
+
+    /// Registers every handler declared with `#[handler]` in the actor
+    #[cfg(feature = "guest")]
+    pub fn register_handlers() {
+        // The registrations are submitted by constructors, which the WebAssembly linker leaves
+        // to the module to run. Running them from `wapc_init` also keeps the linker from running
+        // them again before every call into the actor
+        #[cfg(target_arch = "wasm32")]
+        {
+            extern "C" {
+                fn __wasm_call_ctors();
+            }
+            unsafe { __wasm_call_ctors() };
+        }
+        for registration in inventory::iter::<Registration> {
+            (registration.0)();
+        }
+    }
+}
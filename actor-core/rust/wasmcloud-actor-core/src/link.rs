@@ -0,0 +1,346 @@
+//! # Typed link configuration
+//!
+//! The values a capability provider receives in a [CapabilityConfiguration](../struct.CapabilityConfiguration.html)
+//! are a raw map of strings. This module parses that map into a typed struct, typically through
+//! `#[derive(FromLinkValues)]` (available with the `derive` feature).
+//!
+//! Fields are parsed with [FromLinkValue](trait.FromLinkValue.html), which is implemented for
+//! strings, integers, floats, booleans (`true`/`false`, `yes`/`no`, `on`/`off`, `1`/`0`),
+//! durations (e.g. `500ms`, `30s`, `5m`, `1h`; a bare number is in seconds), comma-separated
+//! lists of any of those, and `Option`s of any of those, which are `None` when the key is absent.
+//!
+//! The derive supports the following attributes:
+//! * `#[link_values(rename_all = "...")]` on the struct renames every key, e.g. to
+//!   `"SCREAMING_SNAKE_CASE"` for environment-style keys. `"lowercase"`, `"UPPERCASE"`,
+//!   `"camelCase"`, `"PascalCase"`, `"kebab-case"` and `"SCREAMING-KEBAB-CASE"` are also supported
+//! * `#[link_values(rename = "...")]` on a field sets its key explicitly
+//! * `#[link_values(default)]` on a field uses `Default::default()` when the key is absent
+//! * `#[link_values(default = "...")]` on a field parses the given value when the key is absent
+//!
+//! Any other field is required. Every missing or invalid key is reported in a single
+//! [LinkValuesError](struct.LinkValuesError.html).
+//!
+//! # Example
+//! ```
+//! use wasmcloud_actor_core::{CapabilityConfiguration, FromLinkValues};
+//! use std::time::Duration;
+//!
+//! #[derive(FromLinkValues)]
+//! #[link_values(rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct RedisConfig {
+//!     url: String,
+//!     #[link_values(default = "30s")]
+//!     timeout: Duration,
+//!     #[link_values(default)]
+//!     pool_size: u32,
+//!     tags: Option<Vec<String>>,
+//! }
+//!
+//! let mut config = CapabilityConfiguration::default();
+//! config.values.insert("URL".to_string(), "redis://0.0.0.0:6379".to_string());
+//! config.values.insert("TAGS".to_string(), "primary, cache".to_string());
+//!
+//! let redis: RedisConfig = config.parse_values().unwrap();
+//! assert_eq!(redis.timeout, Duration::from_secs(30));
+//! assert_eq!(redis.pool_size, 0);
+//! assert_eq!(redis.tags, Some(vec!["primary".to_string(), "cache".to_string()]));
+//!
+//! config.values.insert("POOL_SIZE".to_string(), "lots".to_string());
+//! config.values.remove("URL");
+//! let err = config.parse_values::<RedisConfig>().err().unwrap();
+//! assert_eq!(err.keys(), vec!["URL", "POOL_SIZE"]);
+//! ```
+
+use crate::CapabilityConfiguration;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+/// Types that can be parsed from the full set of link values
+pub trait FromLinkValues: Sized {
+    fn from_link_values(values: &HashMap<String, String>) -> Result<Self, LinkValuesError>;
+}
+
+/// Types that can be parsed from a single link value
+pub trait FromLinkValue: Sized {
+    /// Parses the value, returning a description of what was expected if it is invalid
+    fn from_link_value(value: &str) -> Result<Self, String>;
+
+    /// The value to use when the key is absent. Returning `None` makes the key required
+    fn missing() -> Option<Self> {
+        None
+    }
+}
+
+impl CapabilityConfiguration {
+    /// Parses the link values into a typed configuration struct
+    pub fn parse_values<T: FromLinkValues>(&self) -> Result<T, LinkValuesError> {
+        T::from_link_values(&self.values)
+    }
+}
+
+/// The reason a single key could not be parsed
+#[derive(Debug, PartialEq, Clone)]
+pub enum KeyErrorKind {
+    /// The key is required but was not present
+    Missing,
+    /// The key was present, but its value could not be parsed
+    Invalid { value: String, expected: String },
+}
+
+/// A problem with a single key of the link values
+#[derive(Debug, PartialEq, Clone)]
+pub struct KeyError {
+    pub key: String,
+    pub kind: KeyErrorKind,
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            KeyErrorKind::Missing => write!(f, "missing required key '{}'", self.key),
+            KeyErrorKind::Invalid { value, expected } => write!(
+                f,
+                "invalid value '{}' for key '{}': expected {}",
+                value, self.key, expected
+            ),
+        }
+    }
+}
+
+/// Reports every key of the link values that was missing or invalid
+#[derive(Debug, PartialEq, Clone)]
+pub struct LinkValuesError {
+    pub errors: Vec<KeyError>,
+}
+
+impl LinkValuesError {
+    /// The names of all keys that were missing or invalid, in field order
+    pub fn keys(&self) -> Vec<&str> {
+        self.errors.iter().map(|e| e.key.as_str()).collect()
+    }
+}
+
+impl fmt::Display for LinkValuesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid link values: ")?;
+        for (i, e) in self.errors.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", e)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LinkValuesError {}
+
+/// Collects parsed fields and errors for the code generated by `#[derive(FromLinkValues)]`
+#[doc(hidden)]
+pub struct Fields<'a> {
+    values: &'a HashMap<String, String>,
+    errors: Vec<KeyError>,
+}
+
+#[doc(hidden)]
+impl<'a> Fields<'a> {
+    pub fn new(values: &'a HashMap<String, String>) -> Self {
+        Fields {
+            values,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn get<T: FromLinkValue>(&mut self, key: &str) -> Option<T> {
+        match self.values.get(key) {
+            Some(value) => self.parse(key, value),
+            None => {
+                let missing = T::missing();
+                if missing.is_none() {
+                    self.errors.push(KeyError {
+                        key: key.to_string(),
+                        kind: KeyErrorKind::Missing,
+                    });
+                }
+                missing
+            }
+        }
+    }
+
+    pub fn get_or_default<T: FromLinkValue + Default>(&mut self, key: &str) -> Option<T> {
+        match self.values.get(key) {
+            Some(value) => self.parse(key, value),
+            None => Some(T::default()),
+        }
+    }
+
+    pub fn get_or<T: FromLinkValue>(&mut self, key: &str, default: &str) -> Option<T> {
+        match self.values.get(key) {
+            Some(value) => self.parse(key, value),
+            None => self.parse(key, default),
+        }
+    }
+
+    pub fn finish(self) -> Result<(), LinkValuesError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(LinkValuesError {
+                errors: self.errors,
+            })
+        }
+    }
+
+    fn parse<T: FromLinkValue>(&mut self, key: &str, value: &str) -> Option<T> {
+        match T::from_link_value(value) {
+            Ok(v) => Some(v),
+            Err(expected) => {
+                self.errors.push(KeyError {
+                    key: key.to_string(),
+                    kind: KeyErrorKind::Invalid {
+                        value: value.to_string(),
+                        expected,
+                    },
+                });
+                None
+            }
+        }
+    }
+}
+
+impl FromLinkValue for String {
+    fn from_link_value(value: &str) -> Result<Self, String> {
+        Ok(value.to_string())
+    }
+}
+
+macro_rules! impl_from_link_value_for_number {
+    ($expected:expr, $($t:ty),*) => {
+        $(
+            impl FromLinkValue for $t {
+                fn from_link_value(value: &str) -> Result<Self, String> {
+                    value.trim().parse().map_err(|_| $expected.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_link_value_for_number!(
+    "an integer",
+    u8,
+    u16,
+    u32,
+    u64,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    isize
+);
+impl_from_link_value_for_number!("a number", f32, f64);
+
+impl FromLinkValue for bool {
+    fn from_link_value(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Ok(true),
+            "false" | "no" | "off" | "0" => Ok(false),
+            _ => Err("a boolean".to_string()),
+        }
+    }
+}
+
+impl FromLinkValue for Duration {
+    fn from_link_value(value: &str) -> Result<Self, String> {
+        let expected = || "a duration, e.g. 500ms, 30s, 5m or 1h".to_string();
+        let value = value.trim();
+        let split = value
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(value.len());
+        let (amount, unit) = value.split_at(split);
+        let amount: f64 = amount.parse().map_err(|_| expected())?;
+        let secs = match unit.trim() {
+            "ns" => amount / 1_000_000_000.0,
+            "us" => amount / 1_000_000.0,
+            "ms" => amount / 1_000.0,
+            "" | "s" => amount,
+            "m" => amount * 60.0,
+            "h" => amount * 3_600.0,
+            "d" => amount * 86_400.0,
+            _ => return Err(expected()),
+        };
+        Duration::try_from_secs_f64(secs).map_err(|_| expected())
+    }
+}
+
+impl<T: FromLinkValue> FromLinkValue for Vec<T> {
+    fn from_link_value(value: &str) -> Result<Self, String> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| {
+                T::from_link_value(item).map_err(|e| format!("a comma-separated list of {}", e))
+            })
+            .collect()
+    }
+}
+
+impl<T: FromLinkValue> FromLinkValue for Option<T> {
+    fn from_link_value(value: &str) -> Result<Self, String> {
+        T::from_link_value(value).map(Some)
+    }
+
+    fn missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        let parse = |v| Duration::from_link_value(v).unwrap();
+        assert_eq!(parse("250ms"), Duration::from_millis(250));
+        assert_eq!(parse("30"), Duration::from_secs(30));
+        assert_eq!(parse("1.5s"), Duration::from_millis(1500));
+        assert_eq!(parse("5m"), Duration::from_secs(300));
+        assert_eq!(parse(" 2h "), Duration::from_secs(7200));
+        assert!(Duration::from_link_value("soon").is_err());
+        assert!(Duration::from_link_value("10 parsecs").is_err());
+        assert!(Duration::from_link_value("99999999999999999999999h").is_err());
+    }
+
+    #[test]
+    fn parses_bools_and_lists() {
+        assert_eq!(bool::from_link_value("Yes"), Ok(true));
+        assert_eq!(bool::from_link_value("off"), Ok(false));
+        assert!(bool::from_link_value("maybe").is_err());
+        assert_eq!(Vec::<u16>::from_link_value("80, 443,"), Ok(vec![80, 443]));
+        assert_eq!(Vec::<String>::from_link_value(""), Ok(vec![]));
+        assert!(Vec::<u16>::from_link_value("80,http").is_err());
+    }
+
+    #[test]
+    fn collects_every_error() {
+        let mut values = HashMap::new();
+        values.insert("PORT".to_string(), "eighty".to_string());
+        let mut fields = Fields::new(&values);
+        assert_eq!(fields.get::<String>("HOST"), None);
+        assert_eq!(fields.get::<u16>("PORT"), None);
+        assert_eq!(fields.get::<Option<u16>>("WORKERS"), Some(None));
+        assert_eq!(fields.get_or::<u16>("BACKLOG", "128"), Some(128));
+
+        let err = fields.finish().unwrap_err();
+        assert_eq!(err.keys(), vec!["HOST", "PORT"]);
+        assert_eq!(
+            err.to_string(),
+            "Invalid link values: missing required key 'HOST'; \
+             invalid value 'eighty' for key 'PORT': expected an integer"
+        );
+    }
+}
@@ -0,0 +1,230 @@
+//! # In-process mock host
+//!
+//! When an actor is compiled for a native target (e.g. when running `cargo test`), there is no
+//! waPC host available to answer host calls. In that case every `Host` method in the wasmCloud
+//! interface crates, as well as [call_actor](../fn.call_actor.html), is routed to the mock host
+//! contained in this module instead.
+//!
+//! Tests register an expectation for each `(binding, namespace, operation)` triple they expect
+//! the actor to call, and then drive the actor's registered handlers with [invoke](fn.invoke.html).
+//! Arguments and responses pass through the standard [serialize](../fn.serialize.html) and
+//! [deserialize](../fn.deserialize.html) functions, so the wire format is exercised as well.
+//!
+//! The calls made during a test run can also be saved to a fixture and answered from it by later runs,
+//! see the [replay](replay/index.html) module.
+//!
+//! Expectations and recorded calls are kept per thread, so tests running in parallel do not
+//! interfere with one another. Registered handlers are global, just like they are in a real actor.
+//!
+//! # Example
+//! ```
+//! # use serde::{Deserialize, Serialize};
+//! use wasmcloud_actor_core::{self as actor, testing, HealthCheckRequest, HealthCheckResponse};
+//! use wapc_guest::HandlerResult;
+//!
+//! # #[derive(Serialize, Deserialize)]
+//! # struct GetArgs { key: String }
+//! # #[derive(Serialize, Deserialize)]
+//! # struct GetResponse { value: String, exists: bool }
+//! fn health(_req: HealthCheckRequest) -> HandlerResult<HealthCheckResponse> {
+//!     let args = GetArgs { key: "config".to_string() };
+//!     let res: GetResponse =
+//!         actor::deserialize(&actor::host_call("default", "wasmcloud:keyvalue", "Get", &actor::serialize(args)?)?)?;
+//!     Ok(HealthCheckResponse { healthy: res.exists, message: res.value })
+//! }
+//!
+//! testing::expect("default", "wasmcloud:keyvalue", "Get", |args: GetArgs| {
+//!     assert_eq!(args.key, "config");
+//!     Ok(GetResponse { value: "loaded".to_string(), exists: true })
+//! });
+//! actor::Handlers::register_health_request(health);
+//!
+//! let res: HealthCheckResponse = testing::invoke("HealthRequest", HealthCheckRequest::default()).unwrap();
+//! assert!(res.healthy);
+//! assert_eq!(testing::calls().len(), 1);
+//! ```
+
+pub mod replay;
+
+use crate::{deserialize, serialize};
+use serde::{de::DeserializeOwned, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use wapc_guest::{CallResult, HandlerResult};
+
+type Responder = Box<dyn Fn(&[u8]) -> CallResult>;
+type GuestFunction = fn(&[u8]) -> CallResult;
+
+lazy_static::lazy_static! {
+    static ref FUNCTIONS: RwLock<HashMap<String, GuestFunction>> =
+        RwLock::new(HashMap::new());
+}
+
+thread_local! {
+    static EXPECTATIONS: RefCell<HashMap<(String, String, String), Responder>> =
+        RefCell::new(HashMap::new());
+    static CALLS: RefCell<Vec<HostCall>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A single host call made by the actor while running against the mock host
+#[derive(Debug, PartialEq, Clone)]
+pub struct HostCall {
+    /// The link name the call was made on, e.g. `default`
+    pub binding: String,
+    /// The namespace (capability contract ID) of the call, e.g. `wasmcloud:keyvalue`
+    pub namespace: String,
+    /// The name of the operation, e.g. `Get`
+    pub operation: String,
+    /// The serialized arguments of the call
+    pub payload: Vec<u8>,
+}
+
+/// Registers a typed expectation for the given binding, namespace and operation. The
+/// arguments of matching calls are deserialized into `Req` before being handed to `f`, and
+/// the value it returns is serialized into the response. Registering an expectation for a
+/// triple that already has one replaces it.
+pub fn expect<Req, Resp, F>(binding: &str, namespace: &str, operation: &str, f: F)
+where
+    Req: DeserializeOwned,
+    Resp: Serialize,
+    F: Fn(Req) -> HandlerResult<Resp> + 'static,
+{
+    expect_raw(binding, namespace, operation, move |payload| {
+        let args = deserialize::<Req>(payload)?;
+        serialize(f(args)?)
+    });
+}
+
+/// Registers an expectation that operates on the raw payload bytes. This is useful for
+/// simulating providers that return malformed or incompatible data.
+pub fn expect_raw<F>(binding: &str, namespace: &str, operation: &str, f: F)
+where
+    F: Fn(&[u8]) -> CallResult + 'static,
+{
+    EXPECTATIONS.with(|e| {
+        e.borrow_mut().insert(
+            (
+                binding.to_string(),
+                namespace.to_string(),
+                operation.to_string(),
+            ),
+            Box::new(f),
+        )
+    });
+}
+
+/// Returns every host call made on the current thread since the last [reset](fn.reset.html),
+/// in the order in which they were made
+pub fn calls() -> Vec<HostCall> {
+    CALLS.with(|c| c.borrow().clone())
+}
+
+/// Removes all expectations and recorded calls for the current thread, and stops any recording
+/// or replay
+pub fn reset() {
+    EXPECTATIONS.with(|e| e.borrow_mut().clear());
+    CALLS.with(|c| c.borrow_mut().clear());
+    replay::reset();
+}
+
+/// Invokes the handler registered for `operation` as if the host had delivered `msg` to the
+/// actor, and deserializes the handler's response
+pub fn invoke<Req, Resp>(operation: &str, msg: Req) -> HandlerResult<Resp>
+where
+    Req: Serialize,
+    Resp: DeserializeOwned,
+{
+    let res = invoke_raw(operation, &serialize(msg)?)?;
+    deserialize(&res)
+}
+
+/// Invokes the handler registered for `operation` with a raw payload
+pub fn invoke_raw(operation: &str, payload: &[u8]) -> CallResult {
+    let f = *FUNCTIONS
+        .read()
+        .unwrap()
+        .get(operation)
+        .ok_or_else(|| format!("No handler registered for operation '{}'", operation))?;
+    f(payload)
+}
+
+pub(crate) fn register_function(name: &str, f: GuestFunction) {
+    FUNCTIONS.write().unwrap().insert(name.to_string(), f);
+}
+
+pub(crate) fn host_call(binding: &str, ns: &str, op: &str, msg: &[u8]) -> CallResult {
+    CALLS.with(|c| {
+        c.borrow_mut().push(HostCall {
+            binding: binding.to_string(),
+            namespace: ns.to_string(),
+            operation: op.to_string(),
+            payload: msg.to_vec(),
+        })
+    });
+    let res = replay::answer(binding, ns, op, msg).unwrap_or_else(|| {
+        EXPECTATIONS.with(|e| {
+            let expectations = e.borrow();
+            match expectations.get(&(binding.to_string(), ns.to_string(), op.to_string())) {
+                Some(f) => f(msg),
+                None => Err(format!(
+                    "Unexpected host call: binding '{}', namespace '{}', operation '{}'",
+                    binding, ns, op
+                )
+                .into()),
+            }
+        })
+    });
+    replay::record(binding, ns, op, msg, &res);
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{call_actor, Handlers, HealthCheckRequest, HealthCheckResponse};
+
+    fn health(_req: HealthCheckRequest) -> HandlerResult<HealthCheckResponse> {
+        let message: String = call_actor("echo", "Echo", &"pong".to_string())?;
+        Ok(HealthCheckResponse {
+            healthy: true,
+            message,
+        })
+    }
+
+    #[test]
+    fn invokes_registered_handler() {
+        expect("default", "echo", "Echo", |msg: String| Ok(msg));
+        Handlers::register_health_request(health);
+
+        let res: HealthCheckResponse =
+            invoke("HealthRequest", HealthCheckRequest::default()).unwrap();
+        assert!(res.healthy);
+        assert_eq!(res.message, "pong");
+        assert_eq!(
+            calls(),
+            vec![HostCall {
+                binding: "default".to_string(),
+                namespace: "echo".to_string(),
+                operation: "Echo".to_string(),
+                payload: serialize("pong").unwrap(),
+            }]
+        );
+    }
+
+    #[test]
+    fn unexpected_call_is_an_error() {
+        let res: HandlerResult<String> = call_actor("nobody", "Echo", &"ping".to_string());
+        assert!(res.is_err());
+        assert_eq!(calls().len(), 1);
+        reset();
+        assert!(calls().is_empty());
+    }
+
+    #[test]
+    fn unknown_operation_is_an_error() {
+        let res: HandlerResult<HealthCheckResponse> =
+            invoke("DoesNotExist", HealthCheckRequest::default());
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,501 @@
+//! # Recording and replaying host calls
+//!
+//! A [Recording](struct.Recording.html) captures every host call that the actor makes on the
+//! mock host, along with the response it received, so that the calls can be saved as a fixture
+//! and answered deterministically by later test runs, without the expectations (or the services
+//! they forward to) that originally answered them.
+//!
+//! Fixtures are saved as pretty-printed JSON. Payloads are decoded with the standard
+//! [deserialize](../../fn.deserialize.html) function, so they appear as regular JSON values that
+//! can be read and edited by hand, and are encoded again with [serialize](../../fn.serialize.html)
+//! when they are replayed. Byte arrays appear as arrays of numbers. Payloads that can't be
+//! represented as JSON, such as maps with non-string keys, are kept as raw bytes.
+//!
+//! A replayed call is answered by the first unused exchange with the same binding, namespace,
+//! operation and request, so repeated calls receive their responses in the order in which they
+//! were recorded. Calls that no exchange matches fall through to the registered expectations.
+//!
+//! Requests are compared without the trace context that the interface crates add to them, in
+//! `traceparent` headers or in the `__traceparent` field of `rpc` calls, since every run starts
+//! new traces. See [trace](../../trace/index.html).
+//!
+//! # Example
+//! ```
+//! use wasmcloud_actor_core::{self as actor, testing};
+//! use testing::replay::{self, Recording};
+//!
+//! fn greet(name: &str) -> wapc_guest::HandlerResult<String> {
+//!     let reply = actor::host_call("default", "wasmcloud:example", "Greet", &actor::serialize(name)?)?;
+//!     actor::deserialize(&reply)
+//! }
+//!
+//! # fn main() -> wapc_guest::HandlerResult<()> {
+//! replay::start_recording();
+//! testing::expect("default", "wasmcloud:example", "Greet", |name: String| {
+//!     Ok(format!("Hello, {}!", name))
+//! });
+//! greet("world")?;
+//! let json = replay::stop_recording().to_json();
+//! // Or `save` the recording to a fixture file, and `Recording::load` it in the test
+//!
+//! testing::reset();
+//! replay::replay(Recording::from_json(&json)?);
+//! assert_eq!(greet("world")?, "Hello, world!");
+//! assert!(replay::unreplayed().is_empty());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{deserialize, serialize};
+use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fmt;
+use std::path::Path;
+use wapc_guest::{CallResult, HandlerResult};
+
+thread_local! {
+    static RECORDING: RefCell<Option<Vec<Exchange>>> = const { RefCell::new(None) };
+    static REPLAY: RefCell<Vec<Exchange>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A host call and the response it received
+#[derive(Debug, PartialEq, Clone)]
+pub struct Exchange {
+    /// The link name the call was made on, e.g. `default`
+    pub binding: String,
+    /// The namespace (capability contract ID) of the call, e.g. `wasmcloud:keyvalue`
+    pub namespace: String,
+    /// The name of the operation, e.g. `Get`
+    pub operation: String,
+    /// The serialized arguments of the call
+    pub request: Vec<u8>,
+    /// The serialized response, or the message of the error returned to the actor
+    pub response: Result<Vec<u8>, String>,
+}
+
+/// A sequence of host calls, in the order in which they were made
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Recording {
+    pub exchanges: Vec<Exchange>,
+}
+
+/// The JSON representation of an exchange. An absent payload is an empty one
+#[derive(Serialize, Deserialize)]
+struct Fixture {
+    binding: String,
+    namespace: String,
+    operation: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request: Option<Payload>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    response: Option<Payload>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Payload {
+    Value(serde_json::Value),
+    Raw(Vec<u8>),
+}
+
+impl Payload {
+    fn new(bytes: &[u8]) -> Option<Payload> {
+        if bytes.is_empty() {
+            return None;
+        }
+        Some(match deserialize::<JsonValue>(bytes) {
+            Ok(value) => Payload::Value(value.0),
+            Err(_) => Payload::Raw(bytes.to_vec()),
+        })
+    }
+
+    fn into_bytes(payload: Option<Payload>) -> HandlerResult<Vec<u8>> {
+        match payload {
+            None => Ok(Vec::new()),
+            Some(Payload::Value(value)) => serialize(value),
+            Some(Payload::Raw(bytes)) => Ok(bytes),
+        }
+    }
+}
+
+impl Recording {
+    /// Renders the recording as a pretty-printed JSON fixture
+    pub fn to_json(&self) -> String {
+        let fixtures: Vec<Fixture> = self
+            .exchanges
+            .iter()
+            .map(|e| Fixture {
+                binding: e.binding.clone(),
+                namespace: e.namespace.clone(),
+                operation: e.operation.clone(),
+                request: Payload::new(&e.request),
+                response: e.response.as_ref().ok().and_then(|r| Payload::new(r)),
+                error: e.response.as_ref().err().cloned(),
+            })
+            .collect();
+        serde_json::to_string_pretty(&fixtures).expect("fixtures are representable as JSON")
+    }
+
+    /// Parses a JSON fixture produced by [to_json](#method.to_json)
+    pub fn from_json(json: &str) -> HandlerResult<Recording> {
+        let fixtures: Vec<Fixture> = serde_json::from_str(json)?;
+        let exchanges = fixtures
+            .into_iter()
+            .map(|f| {
+                let response = match f.error {
+                    Some(error) => Err(error),
+                    None => Ok(Payload::into_bytes(f.response)?),
+                };
+                Ok(Exchange {
+                    binding: f.binding,
+                    namespace: f.namespace,
+                    operation: f.operation,
+                    request: Payload::into_bytes(f.request)?,
+                    response,
+                })
+            })
+            .collect::<HandlerResult<_>>()?;
+        Ok(Recording { exchanges })
+    }
+
+    /// Saves the recording as a JSON fixture
+    pub fn save(&self, path: impl AsRef<Path>) -> HandlerResult<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_json())
+            .map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    /// Loads a JSON fixture saved by [save](#method.save)
+    pub fn load(path: impl AsRef<Path>) -> HandlerResult<Recording> {
+        let path = path.as_ref();
+        let json =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Recording::from_json(&json).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+}
+
+/// Starts recording the host calls made on the current thread, discarding any calls recorded
+/// so far
+pub fn start_recording() {
+    RECORDING.with(|r| *r.borrow_mut() = Some(Vec::new()));
+}
+
+/// Stops recording and returns the host calls made on the current thread since
+/// [start_recording](fn.start_recording.html)
+pub fn stop_recording() -> Recording {
+    let exchanges = RECORDING
+        .with(|r| r.borrow_mut().take())
+        .unwrap_or_default();
+    Recording { exchanges }
+}
+
+/// Answers the host calls made on the current thread from the exchanges of a recording. The
+/// exchanges are added to those of any recording that is already being replayed
+pub fn replay(recording: Recording) {
+    REPLAY.with(|r| r.borrow_mut().extend(recording.exchanges));
+}
+
+/// Returns the replayed exchanges that no host call has used yet
+pub fn unreplayed() -> Vec<Exchange> {
+    REPLAY.with(|r| r.borrow().clone())
+}
+
+pub(crate) fn reset() {
+    RECORDING.with(|r| r.borrow_mut().take());
+    REPLAY.with(|r| r.borrow_mut().clear());
+}
+
+/// Answers a host call from the replayed exchanges, if one of them matches it
+pub(crate) fn answer(binding: &str, ns: &str, op: &str, msg: &[u8]) -> Option<CallResult> {
+    REPLAY.with(|r| {
+        let mut exchanges = r.borrow_mut();
+        let pos = exchanges.iter().position(|e| {
+            e.binding == binding
+                && e.namespace == ns
+                && e.operation == op
+                && same_payload(&e.request, msg)
+        })?;
+        Some(exchanges.remove(pos).response.map_err(|e| e.into()))
+    })
+}
+
+/// Records a host call if recording is enabled on the current thread
+pub(crate) fn record(binding: &str, ns: &str, op: &str, msg: &[u8], response: &CallResult) {
+    RECORDING.with(|r| {
+        if let Some(exchanges) = r.borrow_mut().as_mut() {
+            exchanges.push(Exchange {
+                binding: binding.to_string(),
+                namespace: ns.to_string(),
+                operation: op.to_string(),
+                request: msg.to_vec(),
+                response: match response {
+                    Ok(bytes) => Ok(bytes.clone()),
+                    Err(e) => Err(e.to_string()),
+                },
+            })
+        }
+    });
+}
+
+/// Compares two payloads by value, so that a request still matches a fixture that was edited
+/// by hand or encoded differently. The trace context is left out of the comparison
+fn same_payload(a: &[u8], b: &[u8]) -> bool {
+    a == b
+        || match (deserialize::<JsonValue>(a), deserialize::<JsonValue>(b)) {
+            (Ok(JsonValue(mut a)), Ok(JsonValue(mut b))) => {
+                strip_trace_context(&mut a);
+                strip_trace_context(&mut b);
+                a == b
+            }
+            _ => false,
+        }
+}
+
+/// Removes the `traceparent` headers and `__traceparent` fields from a value, at any depth
+fn strip_trace_context(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(object) => {
+            *object = std::mem::take(object)
+                .into_iter()
+                .filter(|(key, _)| {
+                    !key.eq_ignore_ascii_case(crate::trace::TRACEPARENT) && key != "__traceparent"
+                })
+                .collect();
+            object.values_mut().for_each(strip_trace_context);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_trace_context),
+        _ => {}
+    }
+}
+
+/// A JSON value decoded from a payload. Unlike `serde_json::Value`, it accepts byte arrays,
+/// which become arrays of numbers
+struct JsonValue(serde_json::Value);
+
+impl<'de> Deserialize<'de> for JsonValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(JsonValueVisitor)
+    }
+}
+
+struct JsonValueVisitor;
+
+impl<'de> Visitor<'de> for JsonValueVisitor {
+    type Value = JsonValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a value representable as JSON")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<JsonValue, E> {
+        Ok(JsonValue(v.into()))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<JsonValue, E> {
+        Ok(JsonValue(v.into()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<JsonValue, E> {
+        Ok(JsonValue(v.into()))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<JsonValue, E> {
+        Ok(JsonValue(v.into()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<JsonValue, E> {
+        Ok(JsonValue(v.into()))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<JsonValue, E> {
+        Ok(JsonValue(v.to_vec().into()))
+    }
+
+    fn visit_unit<E>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue(serde_json::Value::Null))
+    }
+
+    fn visit_none<E>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue(serde_json::Value::Null))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<JsonValue, D::Error> {
+        JsonValue::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<JsonValue, A::Error> {
+        let mut items = Vec::new();
+        while let Some(JsonValue(item)) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(JsonValue(serde_json::Value::Array(items)))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<JsonValue, A::Error> {
+        let mut object = serde_json::Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let JsonValue(value) = map.next_value()?;
+            object.insert(key, value);
+        }
+        Ok(JsonValue(serde_json::Value::Object(object)))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<JsonValue, D::Error> {
+        JsonValue::deserialize(deserializer)
+    }
+
+    fn visit_enum<A: de::EnumAccess<'de>>(self, _data: A) -> Result<JsonValue, A::Error> {
+        Err(de::Error::custom("enums are not representable as JSON"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::host_call;
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Blob {
+        name: String,
+        #[serde(with = "serde_bytes")]
+        bytes: Vec<u8>,
+        size: Option<u64>,
+    }
+
+    #[test]
+    fn replays_recorded_calls() {
+        start_recording();
+        crate::testing::expect("default", "blobs", "Put", |blob: Blob| Ok(blob.bytes.len()));
+        crate::testing::expect_raw(
+            "default",
+            "blobs",
+            "Fail",
+            |_| Err("not found: gone".into()),
+        );
+        let blob = Blob {
+            name: "a".to_string(),
+            bytes: vec![1, 2, 3],
+            size: None,
+        };
+        let first = host_call("default", "blobs", "Put", &serialize(&blob).unwrap()).unwrap();
+        assert!(host_call("default", "blobs", "Fail", &[]).is_err());
+        let recording = stop_recording();
+        assert_eq!(recording.exchanges.len(), 2);
+
+        let json = recording.to_json();
+        let fixture: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            fixture[0]["request"]["value"],
+            serde_json::json!({ "name": "a", "bytes": [1, 2, 3], "size": null })
+        );
+        assert_eq!(fixture[0]["response"]["value"], 3);
+        assert!(fixture[1].get("request").is_none());
+        assert_eq!(fixture[1]["error"], "not found: gone");
+
+        crate::testing::reset();
+        replay(Recording::from_json(&json).unwrap());
+        let replayed = host_call("default", "blobs", "Put", &serialize(&blob).unwrap()).unwrap();
+        assert_eq!(deserialize::<usize>(&replayed).unwrap(), 3);
+        assert_eq!(replayed, first);
+        let err = host_call("default", "blobs", "Fail", &[]).unwrap_err();
+        assert_eq!(err.to_string(), "not found: gone");
+        assert!(unreplayed().is_empty());
+        // Each exchange answers a single call
+        assert!(host_call("default", "blobs", "Fail", &[]).is_err());
+    }
+
+    #[test]
+    fn matches_requests_by_value() {
+        let json = r#"[{
+            "binding": "default",
+            "namespace": "blobs",
+            "operation": "Get",
+            "request": { "value": { "size": 3, "name": "a", "bytes": [1, 2, 3] } },
+            "response": { "value": "found" }
+        }]"#;
+        replay(Recording::from_json(json).unwrap());
+        let blob = Blob {
+            name: "b".to_string(),
+            bytes: vec![1, 2, 3],
+            size: Some(3),
+        };
+        assert!(host_call("default", "blobs", "Get", &serialize(&blob).unwrap()).is_err());
+        let blob = Blob {
+            name: "a".to_string(),
+            ..blob
+        };
+        let reply = host_call("default", "blobs", "Get", &serialize(&blob).unwrap()).unwrap();
+        assert_eq!(deserialize::<String>(&reply).unwrap(), "found");
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Traced {
+        subject: String,
+        headers: Option<HashMap<String, String>>,
+        __traceparent: Option<String>,
+    }
+
+    /// Makes a call with the trace context of a new trace, as the interface crates do
+    fn traced_call() -> HandlerResult<String> {
+        crate::trace::set_current(Some(crate::trace::TraceContext::root()));
+        let mut headers = HashMap::new();
+        headers.insert("tenant".to_string(), "acme".to_string());
+        let mut args = Traced {
+            subject: "events".to_string(),
+            headers: Some(headers),
+            __traceparent: crate::trace::current().map(|ctx| ctx.to_string()),
+        };
+        crate::trace::inject(&mut args.headers);
+        crate::trace::set_current(None);
+        let reply = host_call("default", "events", "Publish", &serialize(&args)?)?;
+        deserialize(&reply)
+    }
+
+    const FIXTURE_VAR: &str = "REPLAY_TEST_FIXTURE";
+
+    #[test]
+    fn replays_traced_calls_recorded_by_another_process() {
+        // The trace ids of a run are seeded randomly for each process, so the fixture is
+        // recorded by running this test again in a child process
+        if let Ok(path) = std::env::var(FIXTURE_VAR) {
+            start_recording();
+            crate::testing::expect("default", "events", "Publish", |args: Traced| {
+                assert!(args.headers.unwrap().contains_key("traceparent"));
+                Ok(args.subject)
+            });
+            traced_call().unwrap();
+            stop_recording().save(path).unwrap();
+            return;
+        }
+
+        let path = std::env::temp_dir().join(format!("replay-fixture-{}.json", std::process::id()));
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args([
+                "testing::replay::test::replays_traced_calls_recorded_by_another_process",
+                "--exact",
+                "--quiet",
+            ])
+            .env(FIXTURE_VAR, &path)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stdout)
+        );
+        let recording = Recording::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        replay(recording);
+        assert_eq!(traced_call().unwrap(), "events");
+        assert!(unreplayed().is_empty());
+    }
+}
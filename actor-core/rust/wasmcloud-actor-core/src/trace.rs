@@ -0,0 +1,362 @@
+//! # Trace context propagation
+//!
+//! Every invocation delivered to the actor runs with a [TraceContext](struct.TraceContext.html)
+//! that identifies it within a distributed trace, using the ids of the
+//! [W3C Trace Context](https://www.w3.org/TR/trace-context/) `traceparent` header:
+//!
+//! * When the invocation carries a `traceparent`, such as in the headers of an HTTP request or
+//!   a message, or in an actor-to-actor call made through an `rpc` contract, the invocation runs
+//!   in a new span of that trace.
+//! * Otherwise, the invocation starts a new trace.
+//!
+//! The interface crates extract the context of the invocations they deliver and inject the
+//! current context into the outbound calls that can carry it, such as HTTP client requests. The
+//! logging interface adds the trace and span ids to every log line. Types that carry headers can
+//! take part by implementing [Carrier](trait.Carrier.html).
+//!
+//! The context is extracted when the argument of the handler is decoded, so interceptors that run
+//! before the handler see the context of the new trace started for the invocation.
+//!
+//! Ids are generated from a counter mixed with a per-process seed and the payload of the
+//! invocation, which makes them unique enough to correlate calls, but not unpredictable.
+//!
+//! # Example
+//! ```
+//! use wasmcloud_actor_core::trace::{self, TraceContext};
+//! use std::collections::HashMap;
+//!
+//! let mut headers = HashMap::new();
+//! headers.insert(
+//!     "Traceparent".to_string(),
+//!     "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string(),
+//! );
+//! trace::extract(&headers);
+//!
+//! let ctx = trace::current().unwrap();
+//! assert_eq!(ctx.trace_id_hex(), "4bf92f3577b34da6a3ce929d0e0e4736");
+//! assert_eq!(ctx.parent_span_id, Some(0x00f0_67aa_0ba9_02b7));
+//!
+//! let mut outbound: HashMap<String, String> = HashMap::new();
+//! trace::inject(&mut outbound);
+//! assert_eq!(TraceContext::parse(&outbound["traceparent"]).unwrap().span_id, ctx.span_id);
+//! ```
+
+use crate::header::HeaderMap;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The name of the header that carries the trace context
+pub const TRACEPARENT: &str = "traceparent";
+
+const SAMPLED: u8 = 0x01;
+
+lazy_static::lazy_static! {
+    static ref SEED: u64 = RandomState::new().build_hasher().finish();
+}
+
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static CURRENT: Cell<Option<TraceContext>> = const { Cell::new(None) };
+}
+
+/// Identifies a span of work within a distributed trace
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TraceContext {
+    /// The id shared by every span of the trace
+    pub trace_id: u128,
+    /// The id of this span
+    pub span_id: u64,
+    /// The id of the span that this one was started from, if it was received from a caller
+    pub parent_span_id: Option<u64>,
+    /// Whether the caller recorded the trace
+    pub sampled: bool,
+}
+
+impl TraceContext {
+    /// Starts a new, sampled trace
+    pub fn root() -> TraceContext {
+        TraceContext::root_with(0)
+    }
+
+    fn root_with(entropy: u64) -> TraceContext {
+        let trace_id = loop {
+            let id = (u128::from(next_id(entropy)) << 64) | u128::from(next_id(entropy));
+            if id != 0 {
+                break id;
+            }
+        };
+        TraceContext {
+            trace_id,
+            span_id: next_id(entropy),
+            parent_span_id: None,
+            sampled: true,
+        }
+    }
+
+    /// Starts a new span of the same trace, whose parent is this span
+    pub fn child(&self) -> TraceContext {
+        TraceContext {
+            span_id: next_id(0),
+            parent_span_id: Some(self.span_id),
+            ..*self
+        }
+    }
+
+    /// Parses the value of a `traceparent` header, returning `None` if it is invalid. The span
+    /// of the returned context is the caller's span
+    pub fn parse(traceparent: &str) -> Option<TraceContext> {
+        let value = traceparent.trim();
+        let mut parts = value.splitn(5, '-');
+        let version = parse_hex(parts.next()?, 2)?;
+        let trace_id = parse_hex(parts.next()?, 32)?;
+        let span_id = parse_hex(parts.next()?, 16)?;
+        let flags = parse_hex(parts.next()?, 2)?;
+        // Version 0 has exactly four fields, later versions may append more
+        if version == 0xff || (version == 0 && parts.next().is_some()) {
+            return None;
+        }
+        if trace_id == 0 || span_id == 0 {
+            return None;
+        }
+        Some(TraceContext {
+            trace_id,
+            span_id: span_id as u64,
+            parent_span_id: None,
+            sampled: flags as u8 & SAMPLED != 0,
+        })
+    }
+
+    /// The trace id as 32 lowercase hexadecimal digits
+    pub fn trace_id_hex(&self) -> String {
+        format!("{:032x}", self.trace_id)
+    }
+
+    /// The span id as 16 lowercase hexadecimal digits
+    pub fn span_id_hex(&self) -> String {
+        format!("{:016x}", self.span_id)
+    }
+}
+
+/// Formats the context as the value of a `traceparent` header
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = if self.sampled { SAMPLED } else { 0 };
+        write!(
+            f,
+            "00-{:032x}-{:016x}-{:02x}",
+            self.trace_id, self.span_id, flags
+        )
+    }
+}
+
+/// Parses a field of exactly `len` lowercase hexadecimal digits
+fn parse_hex(s: &str, len: usize) -> Option<u128> {
+    if s.len() != len || !s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        return None;
+    }
+    u128::from_str_radix(s, 16).ok()
+}
+
+/// Generates a non-zero id
+fn next_id(entropy: u64) -> u64 {
+    loop {
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let id = splitmix64(*SEED ^ entropy ^ n.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        if id != 0 {
+            return id;
+        }
+    }
+}
+
+fn splitmix64(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Returns the trace context of the current invocation, if any
+pub fn current() -> Option<TraceContext> {
+    CURRENT.with(|c| c.get())
+}
+
+/// Replaces the trace context of the current invocation
+pub fn set_current(ctx: Option<TraceContext>) {
+    CURRENT.with(|c| c.set(ctx));
+}
+
+/// Continues the trace identified by the value of a `traceparent` header, running the rest of
+/// the current invocation in a new span of it. Returns the new context, or `None`, leaving the
+/// current context as it is, if the value is invalid
+pub fn continue_trace(traceparent: &str) -> Option<TraceContext> {
+    let ctx = TraceContext::parse(traceparent)?.child();
+    set_current(Some(ctx));
+    Some(ctx)
+}
+
+/// Continues the trace carried by an inbound message, if it carries one
+pub fn extract<C: Carrier + ?Sized>(carrier: &C) {
+    if let Some(traceparent) = carrier.get(TRACEPARENT) {
+        continue_trace(traceparent);
+    }
+}
+
+/// Attaches the current trace context to an outbound message, if there is one
+pub fn inject<C: Carrier + ?Sized>(carrier: &mut C) {
+    if let Some(ctx) = current() {
+        carrier.set(TRACEPARENT, ctx.to_string());
+    }
+}
+
+/// Runs an invocation in a new trace, restoring the previous context afterwards
+#[cfg_attr(not(feature = "guest"), allow(dead_code))]
+pub(crate) fn invocation<R>(payload: &[u8], f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<TraceContext>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_current(self.0);
+        }
+    }
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    payload.hash(&mut hasher);
+    let _restore = Restore(current());
+    set_current(Some(TraceContext::root_with(hasher.finish())));
+    f()
+}
+
+/// A message that can carry a trace context in its headers. Header names are compared without
+/// regard to case
+pub trait Carrier {
+    /// Returns the value of a header
+    fn get(&self, key: &str) -> Option<&str>;
+
+    /// Sets a header, replacing any value it had
+    fn set(&mut self, key: &str, value: String);
+}
+
+impl Carrier for HashMap<String, String> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        self.retain(|k, _| !k.eq_ignore_ascii_case(key));
+        self.insert(key.to_string(), value);
+    }
+}
+
+impl<'a> Carrier for HashMap<Cow<'a, str>, Cow<'a, str>> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_ref())
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        self.retain(|k, _| !k.eq_ignore_ascii_case(key));
+        self.insert(Cow::Owned(key.to_string()), Cow::Owned(value));
+    }
+}
+
+impl Carrier for HeaderMap {
+    fn get(&self, key: &str) -> Option<&str> {
+        HeaderMap::get(self, key)
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        HeaderMap::insert(self, key, value)
+    }
+}
+
+impl<C: Carrier> Carrier for Option<C>
+where
+    C: Default,
+{
+    fn get(&self, key: &str) -> Option<&str> {
+        self.as_ref()?.get(key)
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        self.get_or_insert_with(C::default).set(key, value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_traceparent() {
+        let ctx =
+            TraceContext::parse("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01").unwrap();
+        assert_eq!(ctx.trace_id, 0x0af7651916cd43dd8448eb211c80319c);
+        assert_eq!(ctx.span_id_hex(), "b7ad6b7169203331");
+        assert!(ctx.sampled);
+        assert_eq!(
+            ctx.to_string(),
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01"
+        );
+
+        let future = "01-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-00-extra";
+        assert!(!TraceContext::parse(future).unwrap().sampled);
+
+        for invalid in [
+            "",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331",
+            "00-0AF7651916CD43DD8448EB211C80319C-b7ad6b7169203331-01",
+            "00-00000000000000000000000000000000-b7ad6b7169203331-01",
+            "00-0af7651916cd43dd8448eb211c80319c-0000000000000000-01",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01-extra",
+            "ff-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+            "00-0af7651916cd43dd8448eb211c8031-b7ad6b7169203331-01",
+        ]
+        .iter()
+        {
+            assert_eq!(TraceContext::parse(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn scopes_invocations() {
+        set_current(None);
+        let (outer, inner) = invocation(b"payload", || {
+            let outer = current().unwrap();
+            assert_eq!(outer.parent_span_id, None);
+            let inner = invocation(b"payload", || {
+                continue_trace(&outer.to_string());
+                current().unwrap()
+            });
+            assert_eq!(current(), Some(outer));
+            (outer, inner)
+        });
+        assert_eq!(current(), None);
+        assert_eq!(inner.trace_id, outer.trace_id);
+        assert_eq!(inner.parent_span_id, Some(outer.span_id));
+        assert_ne!(inner.span_id, outer.span_id);
+    }
+
+    #[test]
+    fn carries_headers() {
+        let ctx = TraceContext::root();
+        set_current(Some(ctx));
+        let mut headers: Option<HashMap<String, String>> = None;
+        inject(&mut headers);
+        assert_eq!(headers.get("TraceParent"), Some(ctx.to_string().as_str()));
+        inject(&mut headers);
+        assert_eq!(headers.as_ref().unwrap().len(), 1);
+
+        set_current(None);
+        extract(&headers);
+        assert_eq!(current().unwrap().parent_span_id, Some(ctx.span_id));
+    }
+}
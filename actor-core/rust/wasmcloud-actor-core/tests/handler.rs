@@ -0,0 +1,11 @@
+//! Checks the handlers declared with `#[actor::handler]`, which are registered automatically and
+//! whose mistakes must be reported on the handler function
+
+#[cfg(feature = "guest")]
+#[test]
+fn registers_and_checks_handlers() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/registers-handlers-in-modules.rs");
+    t.compile_fail("tests/ui/wrong-signature.rs");
+    t.compile_fail("tests/ui/init-arguments.rs");
+}
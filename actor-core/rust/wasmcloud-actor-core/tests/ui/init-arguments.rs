@@ -0,0 +1,6 @@
+extern crate wasmcloud_actor_core as actor;
+
+#[actor::init(handlers(health))]
+fn init() {}
+
+fn main() {}
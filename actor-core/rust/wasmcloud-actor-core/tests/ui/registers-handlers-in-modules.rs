@@ -0,0 +1,24 @@
+extern crate wasmcloud_actor_core as actor;
+
+mod health {
+    use actor::{HealthCheckRequest, HealthCheckResponse};
+    use wapc_guest::HandlerResult;
+
+    #[actor::handler(actor::HealthRequest)]
+    fn health(_req: HealthCheckRequest) -> HandlerResult<HealthCheckResponse> {
+        Ok(HealthCheckResponse {
+            healthy: false,
+            message: "not ready".to_string(),
+        })
+    }
+}
+
+#[actor::init]
+fn init() {}
+
+fn main() {
+    wapc_init();
+    let res: actor::HealthCheckResponse =
+        actor::testing::invoke("HealthRequest", actor::HealthCheckRequest::default()).unwrap();
+    assert_eq!(res.message, "not ready");
+}
@@ -0,0 +1,13 @@
+extern crate wasmcloud_actor_core as actor;
+
+use wapc_guest::HandlerResult;
+
+#[actor::handler(actor::HealthRequest)]
+fn health(_req: String) -> HandlerResult<actor::HealthCheckResponse> {
+    Ok(actor::HealthCheckResponse::healthy())
+}
+
+#[actor::init]
+fn init() {}
+
+fn main() {}
@@ -0,0 +1,9 @@
+fn main() {
+    wasmcloud_widl_codegen::Generator::new("blobstore.widl")
+        .host_role("Store")
+        .provider_role("Store", "BlobStore")
+        .handler_role("Actor")
+        .borrowed_type("FileChunk")
+        .build()
+        .unwrap();
+}
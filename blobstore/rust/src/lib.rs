@@ -52,11 +52,40 @@
 //! # }
 //!
 //! ```
+//!
+//! Actors that receive large chunks can register with `register_receive_chunk_ref` instead of
+//! `register_receive_chunk`, and receive a [FileChunkRef](struct.FileChunkRef.html) whose bytes borrow
+//! from the payload delivered by the host rather than being copied out of it.
+//!
+//! # Errors
+//!
+//! Operations that return a `BlobstoreResult` can be converted into a `Result` with a structured
+//! `ActorError` via `IntoActorResult`, so callers can match on the kind of failure:
+//!
+//! ```rust
+//! # use wasmcloud_actor_blobstore::BlobstoreResult;
+//! use wasmcloud_actor_core::{ErrorKind, IntoActorResult};
+//!
+//! let res = BlobstoreResult {
+//!     success: false,
+//!     error: Some("not_found: no such container".to_string()),
+//! };
+//! assert_eq!(res.into_result().unwrap_err().kind(), ErrorKind::NotFound);
+//! ```
+//!
+//! # Capability providers
+//! The `provider` feature exposes the [BlobStore](trait.BlobStore.html) trait, which capability providers implement
+//! for the blob store operations, such as creating containers and receiving uploaded chunks, and a
+//! [dispatch](fn.dispatch.html) function that routes an actor's call to it.
 
-mod generated;
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+}
 #[allow(unused_imports)]
 pub use generated::*;
 
+use wasmcloud_actor_core::{ActorError, IntoActorResult};
+
 /// Guest sends a Container to the capability provider, receives a Container back
 pub const OP_CREATE_CONTAINER: &str = "CreateContainer";
 /// Guest sends a Container to the capability provider, lack of error indicates success
@@ -82,3 +111,88 @@ impl Container {
         Container { id: id.into() }
     }
 }
+
+impl IntoActorResult for BlobstoreResult {
+    type Ok = ();
+
+    /// Recovers the kind of the error reported by the provider, if it used the format of
+    /// `ActorError`. A failure without an error message is reported as an `Internal` error
+    fn into_result(self) -> Result<(), ActorError> {
+        match (self.success, self.error) {
+            (true, _) => Ok(()),
+            (false, Some(e)) => Err(ActorError::parse(&e)),
+            (false, None) => Err(ActorError::internal("blobstore operation failed")),
+        }
+    }
+}
+
+impl From<Result<(), ActorError>> for BlobstoreResult {
+    fn from(res: Result<(), ActorError>) -> Self {
+        match res {
+            Ok(()) => BlobstoreResult {
+                success: true,
+                error: None,
+            },
+            Err(e) => BlobstoreResult {
+                success: false,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "guest")]
+mod file_chunk_ref {
+    extern crate wapc_guest;
+    use crate::{serialize, Container, FileChunk, FileChunkRef, Handlers, ReceiveChunkArgs};
+    use std::sync::Mutex;
+    use wapc_guest::HandlerResult;
+    use wasmcloud_actor_core::testing;
+
+    static RECEIVED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    #[test]
+    fn borrows_chunk_bytes_from_payload() {
+        Handlers::register_receive_chunk_ref(record);
+        let chunk = FileChunk {
+            sequence_no: 2,
+            container: Container::new("photos"),
+            id: "cat.png".to_string(),
+            total_bytes: 10,
+            chunk_size: 5,
+            context: Some("upload-7".to_string()),
+            chunk_bytes: b"hello".to_vec(),
+        };
+
+        let args = ReceiveChunkArgs {
+            chunk: chunk.clone(),
+        };
+        testing::invoke::<_, ()>("ReceiveChunk", &args).unwrap();
+        assert_eq!(
+            *RECEIVED.lock().unwrap(),
+            ["photos/cat.png #2 upload-7 hello"]
+        );
+
+        let payload = serialize(&chunk).unwrap();
+        let borrowed: FileChunkRef =
+            wasmcloud_actor_core::codec::deserialize_borrowed(&payload).unwrap();
+        assert!(payload
+            .as_ptr_range()
+            .contains(&borrowed.chunk_bytes.as_ptr()));
+        assert_eq!(borrowed.into_owned(), chunk);
+        assert_eq!(FileChunkRef::from(&chunk).into_owned(), chunk);
+    }
+
+    fn record(chunk: FileChunkRef) -> HandlerResult<()> {
+        RECEIVED.lock().unwrap().push(format!(
+            "{}/{} #{} {} {}",
+            chunk.container.id,
+            chunk.id,
+            chunk.sequence_no,
+            chunk.context.unwrap_or_default(),
+            String::from_utf8_lossy(chunk.chunk_bytes)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+//! The document model produced by the WIDL [parser](../fn.parse.html)
+
+/// A parsed WIDL schema
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Document {
+    /// The namespace of the interface, e.g. `wasmcloud:keyvalue`
+    pub namespace: String,
+    pub roles: Vec<Role>,
+    pub types: Vec<TypeDefinition>,
+}
+
+impl Document {
+    pub fn role(&self, name: &str) -> Option<&Role> {
+        self.roles.iter().find(|r| r.name == name)
+    }
+
+    pub fn type_definition(&self, name: &str) -> Option<&TypeDefinition> {
+        self.types.iter().find(|t| t.name == name)
+    }
+}
+
+/// A named group of operations, such as the operations a capability provider offers to actors
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Role {
+    pub name: String,
+    pub description: Option<String>,
+    pub operations: Vec<Operation>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Operation {
+    pub name: String,
+    pub description: Option<String>,
+    pub parameters: Vec<Field>,
+    /// Whether the operation was declared with braces, e.g. `HandleRequest{request: Request}`,
+    /// in which case its single parameter is sent as is instead of being wrapped in an
+    /// arguments struct
+    pub unary: bool,
+    pub returns: Type,
+}
+
+/// A `type` declaration
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct TypeDefinition {
+    pub name: String,
+    pub description: Option<String>,
+    pub fields: Vec<Field>,
+}
+
+/// A field of a type or a parameter of an operation
+#[derive(Debug, PartialEq, Clone)]
+pub struct Field {
+    pub name: String,
+    pub description: Option<String>,
+    pub ty: Type,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Type {
+    /// A scalar such as `string` or `u64`, or a reference to a type declared in the schema
+    Named(String),
+    /// `[T]`
+    List(Box<Type>),
+    /// `{K: V}`
+    Map(Box<Type>, Box<Type>),
+    /// `T?`
+    Optional(Box<Type>),
+    /// `void`, only valid as the return type of an operation
+    Void,
+}
+
+/// The scalar types understood by the generator
+pub const SCALARS: [&str; 13] = [
+    "bool", "i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "f32", "f64", "string", "bytes",
+];
@@ -0,0 +1,530 @@
+//! # wasmCloud WIDL Code Generator
+//!
+//! This crate parses the WIDL schemas that describe wasmCloud actor interfaces and generates
+//! the Rust bindings used by the interface crates: the `Host` client for the operations of a
+//! capability provider, the `Handlers` that actors use to register functions for operations
+//! delivered to them, the argument structs, the types declared in the schema and the
+//! `serialize`/`deserialize` codec functions.
+//!
+//! The generator is meant to be run from a build script, so an interface crate's bindings are
+//! regenerated whenever its schema changes and can never drift away from it.
+//!
+//! # Example
+//! In the `main` function of `build.rs`:
+//! ```no_run
+//! wasmcloud_widl_codegen::Generator::new("messaging.widl")
+//!     .host_role("Broker")
+//!     .handler_role("Consumer")
+//!     .build()
+//!     .unwrap();
+//! ```
+//!
+//! And in `lib.rs`:
+//! ```ignore
+//! mod generated {
+//!     include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+//! }
+//! pub use generated::*;
+//! ```
+//!
+//! The generated code depends on `serde`, `serde_bytes`, `lazy_static`, `wapc-guest` (behind
+//! the crate's `guest` feature) and `wasmcloud-actor-core`.
+
+pub mod ast;
+mod parser;
+mod rust;
+
+pub use parser::{parse, ParseError};
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Errors produced while generating bindings
+#[derive(Debug)]
+pub enum Error {
+    /// The schema or the generated file could not be read or written
+    Io(PathBuf, std::io::Error),
+    /// The schema is not valid WIDL
+    Parse(PathBuf, ParseError),
+    /// The schema is valid WIDL, but can't be turned into bindings as configured
+    Schema(PathBuf, String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            Error::Parse(path, e) => write!(f, "{}: {}", path.display(), e),
+            Error::Schema(path, e) => write!(f, "{}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(_, e) => Some(e),
+            Error::Parse(_, e) => Some(e),
+            Error::Schema(..) => None,
+        }
+    }
+}
+
+/// Generates the Rust bindings for a WIDL schema
+#[derive(Debug, Clone)]
+pub struct Generator {
+    schema: PathBuf,
+    host_roles: Vec<String>,
+    handler_roles: Vec<String>,
+    core_crate: String,
+    internal_operations: Vec<String>,
+    custom_host: bool,
+    custom_types: Vec<String>,
+    provider: Option<(String, String)>,
+    traced_operations: Vec<String>,
+    borrowed_types: Vec<String>,
+}
+
+impl Generator {
+    /// Creates a generator for the schema at the given path, which is relative to the crate
+    /// root when run from a build script
+    pub fn new(schema: impl AsRef<Path>) -> Generator {
+        Generator {
+            schema: schema.as_ref().to_path_buf(),
+            host_roles: Vec::new(),
+            handler_roles: Vec::new(),
+            core_crate: "wasmcloud_actor_core".to_string(),
+            internal_operations: Vec::new(),
+            custom_host: false,
+            custom_types: Vec::new(),
+            provider: None,
+            traced_operations: Vec::new(),
+            borrowed_types: Vec::new(),
+        }
+    }
+
+    /// Generates `Host` methods for the operations of a role that actors invoke on the host
+    pub fn host_role(mut self, role: &str) -> Generator {
+        self.host_roles.push(role.to_string());
+        self
+    }
+
+    /// Generates `Handlers::register_*` functions for the operations of a role that the host
+    /// invokes on actors
+    pub fn handler_role(mut self, role: &str) -> Generator {
+        self.handler_roles.push(role.to_string());
+        self
+    }
+
+    /// Sets the path through which the generated code refers to `wasmcloud-actor-core`, which
+    /// defaults to `wasmcloud_actor_core`
+    pub fn core_crate(mut self, path: &str) -> Generator {
+        self.core_crate = path.to_string();
+        self
+    }
+
+    /// Generates the `Host` method for an operation as a `pub(crate)` method prefixed with an
+    /// underscore, so the crate can expose its own wrapper under the operation's name
+    pub fn internal_operation(mut self, operation: &str) -> Generator {
+        self.internal_operations.push(operation.to_string());
+        self
+    }
+
+    /// Leaves the declaration of the `Host` struct and of the `host` and `default` functions to
+    /// the root of the crate, which must also give `Host` a `binding(&self) -> String` method that returns
+    /// the link name to call. The generated `Host` methods are still implemented for it
+    pub fn custom_host(mut self) -> Generator {
+        self.custom_host = true;
+        self
+    }
+
+    /// Leaves the declaration of a type of the schema to the root of the crate, from which the
+    /// generated code re-exports it, e.g. to give a field a richer Rust type than the schema can
+    /// express. The crate's type must serialize to
+    /// the fields declared in the schema, which the `CONTRACT` descriptor keeps describing
+    pub fn custom_type(mut self, name: &str) -> Generator {
+        self.custom_types.push(name.to_string());
+        self
+    }
+
+    /// Generates, behind the crate's `provider` feature, a trait with the given name that
+    /// capability providers implement for the operations of a host role, along with a
+    /// `dispatch` function that routes an actor's call to the matching trait method
+    pub fn provider_role(mut self, role: &str, trait_name: &str) -> Generator {
+        self.provider = Some((role.to_string(), trait_name.to_string()));
+        self
+    }
+
+    /// Propagates the current trace context through an operation that carries headers. The
+    /// `Host` method of a host operation attaches the context to its arguments before sending
+    /// them, while the handler wrapper of a handler operation continues the trace carried by the
+    /// argument it receives. The arguments struct, or the single argument of a unary operation,
+    /// must implement `wasmcloud_actor_core::trace::Carrier`
+    pub fn trace_operation(mut self, operation: &str) -> Generator {
+        self.traced_operations.push(operation.to_string());
+        self
+    }
+
+    /// Generates a `<Name>Ref<'a>` variant of a type of the schema whose strings and bytes borrow
+    /// from the payload it is decoded from, along with a `Handlers::register_*_ref` function for
+    /// every handler operation that receives the type. Unless the type is a custom type, its
+    /// `into_owned` method and `From<&'a Name>` conversion are generated too
+    pub fn borrowed_type(mut self, name: &str) -> Generator {
+        self.borrowed_types.push(name.to_string());
+        self
+    }
+
+    /// Parses the schema and returns the generated source
+    pub fn generate(&self) -> Result<String, Error> {
+        let src =
+            std::fs::read_to_string(&self.schema).map_err(|e| Error::Io(self.schema.clone(), e))?;
+        self.generate_from_str(&src)
+    }
+
+    /// Generates the bindings for the source of a schema
+    pub fn generate_from_str(&self, src: &str) -> Result<String, Error> {
+        let doc = parse(src).map_err(|e| Error::Parse(self.schema.clone(), e))?;
+        let roles: Vec<String> = self
+            .host_roles
+            .iter()
+            .chain(self.handler_roles.iter())
+            .cloned()
+            .collect();
+        rust::validate(&doc, &roles).map_err(|e| Error::Schema(self.schema.clone(), e))?;
+        for op in self.internal_operations.iter() {
+            let declared = self
+                .host_roles
+                .iter()
+                .filter_map(|r| doc.role(r))
+                .any(|r| r.operations.iter().any(|o| &o.name == op));
+            if !declared {
+                return Err(Error::Schema(
+                    self.schema.clone(),
+                    format!("no host role declares operation `{}`", op),
+                ));
+            }
+        }
+        for ty in self.custom_types.iter() {
+            if !doc.types.iter().any(|t| &t.name == ty) {
+                return Err(Error::Schema(
+                    self.schema.clone(),
+                    format!("custom type `{}` is not declared in the schema", ty),
+                ));
+            }
+        }
+        for ty in self.borrowed_types.iter() {
+            if !doc.types.iter().any(|t| &t.name == ty) {
+                return Err(Error::Schema(
+                    self.schema.clone(),
+                    format!("borrowed type `{}` is not declared in the schema", ty),
+                ));
+            }
+        }
+        for op in self.traced_operations.iter() {
+            let declared = roles
+                .iter()
+                .filter_map(|r| doc.role(r))
+                .flat_map(|r| r.operations.iter())
+                .find(|o| &o.name == op);
+            match declared {
+                None => {
+                    return Err(Error::Schema(
+                        self.schema.clone(),
+                        format!("no configured role declares operation `{}`", op),
+                    ))
+                }
+                Some(o) if o.parameters.is_empty() => {
+                    return Err(Error::Schema(
+                        self.schema.clone(),
+                        format!(
+                            "traced operation `{}` has no arguments to carry a trace",
+                            op
+                        ),
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+        if let Some((role, _)) = &self.provider {
+            if !self.host_roles.contains(role) {
+                return Err(Error::Schema(
+                    self.schema.clone(),
+                    format!("provider role `{}` is not a host role", role),
+                ));
+            }
+        }
+        Ok(rust::generate(self, &doc))
+    }
+
+    /// Generates the bindings into `$OUT_DIR/generated.rs` and tells Cargo to rerun the build
+    /// script when the schema changes. Only call this from a build script
+    pub fn build(&self) -> Result<(), Error> {
+        println!("cargo:rerun-if-changed={}", self.schema.display());
+        let out_dir = std::env::var_os("OUT_DIR").expect("OUT_DIR is set by Cargo");
+        let path = Path::new(&out_dir).join("generated.rs");
+        let src = self.generate()?;
+        std::fs::write(&path, src).map_err(|e| Error::Io(path, e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+namespace "wasmcloud:example"
+
+role Store {
+    "Gets a value"
+    Get(key: string, payload: bytes): GetResponse
+    Clear(): void
+}
+
+role Actor {
+    Deliver{event: GetResponse}: void
+}
+
+type GetResponse {
+    value: string?
+}
+"#;
+
+    #[test]
+    fn generates_host_and_handlers() {
+        let src = Generator::new("example.widl")
+            .host_role("Store")
+            .handler_role("Actor")
+            .generate_from_str(SCHEMA)
+            .unwrap();
+        assert!(src.contains(
+            "pub fn get(&self, key: String, payload: Vec<u8>) -> HandlerResult<GetResponse>"
+        ));
+        assert!(src.contains("\"wasmcloud:example\", \"Get\", &serialize(input_args)?"));
+        assert!(src.contains("pub fn clear(&self) -> HandlerResult<()>"));
+        assert!(src.contains(
+            "failed to decode the reply to `Get` from `wasmcloud:example` as `GetResponse`: {}"
+        ));
+        assert!(!src.contains("deserialize::<GetResponse>(vec.as_ref()).unwrap()"));
+        assert!(src.contains("pub struct GetArgs {"));
+        assert!(
+            src.contains("#[serde(with = \"serde_bytes\")]\n    #[serde(rename = \"payload\")]")
+        );
+        assert!(src.contains(
+            "pub fn register_deliver(f: impl Fn(GetResponse) -> HandlerResult<()> + Send + Sync + 'static)"
+        ));
+        assert!(src.contains(
+            "type DeliverHandler = std::sync::Arc<dyn Fn(GetResponse) -> HandlerResult<()> + Send + Sync>;"
+        ));
+        assert!(src.contains("let input = deserialize::<GetResponse>(input_payload)?;"));
+        assert!(src.contains(
+            "wasmcloud_actor_core::interceptor::invocation(\"Deliver\", input_payload, |input_payload| {"
+        ));
+        assert!(!src.contains("DeliverArgs"));
+        assert!(src.contains("pub value: Option<String>,"));
+        assert!(src.contains("wasmcloud_actor_core::codec::serialize(item)"));
+    }
+
+    #[test]
+    fn generates_provider_dispatch() {
+        let src = Generator::new("example.widl")
+            .host_role("Store")
+            .provider_role("Store", "ExampleStore")
+            .generate_from_str(SCHEMA)
+            .unwrap();
+        assert!(src.contains("#[cfg(feature = \"provider\")]\npub trait ExampleStore {"));
+        assert!(src.contains(
+            "fn get(&self, actor: &str, key: String, payload: Vec<u8>) -> Result<GetResponse, "
+        ));
+        assert!(src.contains("pub fn dispatch<T: ExampleStore + ?Sized>("));
+        assert!(src.contains("serialize(provider.get(actor, input.key, input.payload)?)"));
+        assert!(src.contains("provider.clear(actor)?;\n            Ok(Vec::new())"));
+        assert!(src.contains("unknown operation `{}` for wasmcloud:example"));
+
+        let err = Generator::new("example.widl")
+            .provider_role("Store", "ExampleStore")
+            .generate_from_str(SCHEMA)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("provider role `Store` is not a host role"));
+    }
+
+    #[test]
+    fn generates_contract() {
+        let src = Generator::new("example.widl")
+            .host_role("Store")
+            .handler_role("Actor")
+            .generate_from_str(SCHEMA)
+            .unwrap();
+        assert!(src.contains("pub static CONTRACT: wasmcloud_actor_core::contract::Contract"));
+        assert!(src.contains("capability_id: \"wasmcloud:example\","));
+        assert!(src.contains("version: env!(\"CARGO_PKG_VERSION\"),"));
+        assert!(src.contains(
+            "name: \"Store\",\n            description: None,\n            direction: wasmcloud_actor_core::contract::Direction::ToProvider,"
+        ));
+        assert!(src.contains("direction: wasmcloud_actor_core::contract::Direction::ToActor,"));
+        assert!(src.contains("description: Some(\"Gets a value\"),"));
+        assert!(src.contains("ty: wasmcloud_actor_core::contract::Type::Named(\"bytes\"),"));
+        assert!(src.contains("unary: true,"));
+        assert!(src.contains(
+            "ty: wasmcloud_actor_core::contract::Type::Optional(&wasmcloud_actor_core::contract::Type::Named(\"string\")),"
+        ));
+        assert!(src.contains("returns: wasmcloud_actor_core::contract::Type::Void,"));
+    }
+
+    #[test]
+    fn generates_trace_propagation() {
+        let src = Generator::new("example.widl")
+            .host_role("Store")
+            .handler_role("Actor")
+            .trace_operation("Get")
+            .trace_operation("Deliver")
+            .generate_from_str(SCHEMA)
+            .unwrap();
+        assert!(src.contains(
+            "let mut input_args = GetArgs { key, payload };\n        \
+             wasmcloud_actor_core::trace::inject(&mut input_args);"
+        ));
+        assert!(src.contains(
+            "let input = deserialize::<GetResponse>(input_payload)?;\n        \
+             wasmcloud_actor_core::trace::extract(&input);"
+        ));
+
+        let err = Generator::new("example.widl")
+            .host_role("Store")
+            .trace_operation("Clear")
+            .generate_from_str(SCHEMA)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("traced operation `Clear` has no arguments to carry a trace"));
+    }
+
+    #[test]
+    fn generates_borrowed_types() {
+        let src = Generator::new("example.widl")
+            .handler_role("Actor")
+            .borrowed_type("GetResponse")
+            .trace_operation("Deliver")
+            .generate_from_str(SCHEMA)
+            .unwrap();
+        assert!(src.contains(
+            "    #[serde(borrow, default)]\n    \
+             pub value: Option<std::borrow::Cow<'a, str>>,"
+        ));
+        assert!(src.contains("value: self.value.map(|v| v.into_owned()),"));
+        assert!(src.contains(
+            "value: value.value.as_ref().map(|v| std::borrow::Cow::Borrowed(v.as_str())),"
+        ));
+        assert!(src.contains(
+            "pub fn register_deliver_ref(f: impl Fn(GetResponseRef) -> HandlerResult<()> + Send \
+             + Sync + 'static)"
+        ));
+        assert!(src.contains(
+            "let input = wasmcloud_actor_core::codec::deserialize_borrowed::<GetResponseRef>\
+             (input_payload)?;\n        wasmcloud_actor_core::trace::extract(&input);"
+        ));
+
+        // The single argument of an operation declared with parentheses is wrapped in a struct
+        let src = Generator::new("example.widl")
+            .handler_role("Actor")
+            .borrowed_type("Chunk")
+            .custom_type("Chunk")
+            .generate_from_str(
+                "namespace \"a\"\nrole Actor { Receive(chunk: Chunk): void }\n\
+                 type Chunk { tags: {string: [string]}\n data: bytes }",
+            )
+            .unwrap();
+        assert!(src.contains(
+            "pub tags: std::collections::HashMap<std::borrow::Cow<'a, str>, \
+             Vec<std::borrow::Cow<'a, str>>>,"
+        ));
+        assert!(src.contains("pub data: &'a [u8],"));
+        assert!(src.contains("struct ReceiveArgsRef<'a> {"));
+        assert!(src.contains("f(input.chunk)?;"));
+        assert!(!src.contains("fn into_owned"));
+
+        let err = Generator::new("example.widl")
+            .borrowed_type("Missing")
+            .generate_from_str(SCHEMA)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("borrowed type `Missing` is not declared in the schema"));
+    }
+
+    #[test]
+    fn leaves_custom_types_to_the_crate() {
+        let src = Generator::new("example.widl")
+            .host_role("Store")
+            .custom_type("GetResponse")
+            .generate_from_str(SCHEMA)
+            .unwrap();
+        assert!(src.contains("pub use crate::GetResponse;"));
+        assert!(!src.contains("pub struct GetResponse"));
+        assert!(src.contains("name: \"GetResponse\""));
+
+        let err = Generator::new("example.widl")
+            .custom_type("Missing")
+            .generate_from_str(SCHEMA)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("custom type `Missing` is not declared in the schema"));
+    }
+
+    #[test]
+    fn validates_the_configuration() {
+        let err = Generator::new("example.widl")
+            .host_role("Missing")
+            .generate_from_str(SCHEMA)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "example.widl: the schema does not declare role `Missing`"
+        );
+
+        let err = Generator::new("example.widl")
+            .host_role("Store")
+            .generate_from_str("namespace \"a\"\nrole Store { Get(): Missing }")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "example.widl: operation `Get` refers to unknown type `Missing`"
+        );
+
+        let err = Generator::new("example.widl")
+            .handler_role("Actor")
+            .internal_operation("Deliver")
+            .generate_from_str(SCHEMA)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("no host role declares operation `Deliver`"));
+    }
+
+    #[test]
+    fn parses_every_interface_schema() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let schemas = [
+            "actor-core/core.widl",
+            "blobstore/blobstore.widl",
+            "eventstreams/eventstreams.widl",
+            "extras/extras.widl",
+            "graphdb/graphdb.widl",
+            "http-client/httpclient.widl",
+            "http-server/httpserver.widl",
+            "keyvalue/keyvalue.widl",
+            "logging/logging.widl",
+            "messaging/messaging.widl",
+            "metrics/metrics.widl",
+            "telnet/telnet.widl",
+        ];
+        for schema in schemas.iter() {
+            let src = std::fs::read_to_string(root.join(schema)).unwrap();
+            if let Err(e) = parse(&src) {
+                panic!("{}: {}", schema, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,484 @@
+use crate::ast::{Document, Field, Operation, Role, Type, TypeDefinition};
+use std::fmt;
+
+/// An error in a WIDL schema, with the line it was found on
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Punct(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "`{}`", s),
+            Token::Str(_) => f.write_str("a description"),
+            Token::Punct(c) => write!(f, "`{}`", c),
+        }
+    }
+}
+
+/// Parses the source of a WIDL schema
+pub fn parse(src: &str) -> Result<Document, ParseError> {
+    let tokens = tokenize(src)?;
+    let last_line = src.lines().count().max(1);
+    Parser {
+        tokens,
+        pos: 0,
+        last_line,
+    }
+    .document()
+}
+
+fn tokenize(src: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '#' => {
+                while matches!(chars.peek(), Some(c) if *c != '\n') {
+                    chars.next();
+                }
+            }
+            '"' => {
+                let start = line;
+                let block = chars.peek() == Some(&'"') && {
+                    chars.next();
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                        true
+                    } else {
+                        // An empty description
+                        tokens.push((Token::Str(String::new()), start));
+                        continue;
+                    }
+                };
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        None => {
+                            return Err(ParseError {
+                                line: start,
+                                message: "unterminated description".to_string(),
+                            })
+                        }
+                        Some('"') if !block => break,
+                        Some('"') if block && text.ends_with("\"\"") => {
+                            text.truncate(text.len() - 2);
+                            break;
+                        }
+                        Some('\n') if !block => {
+                            return Err(ParseError {
+                                line: start,
+                                message: "unterminated description".to_string(),
+                            })
+                        }
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            text.push(c);
+                        }
+                    }
+                }
+                tokens.push((Token::Str(text), start));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut ident = c.to_string();
+                while let Some(c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                    ident.push(*c);
+                    chars.next();
+                }
+                tokens.push((Token::Ident(ident), line));
+            }
+            '{' | '}' | '(' | ')' | '[' | ']' | ':' | ',' | '?' | '@' | '=' => {
+                tokens.push((Token::Punct(c), line))
+            }
+            c => {
+                return Err(ParseError {
+                    line,
+                    message: format!("unexpected character `{}`", c),
+                })
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Turns the text of a description into paragraphs of single-spaced lines, separated by a
+/// blank line
+fn normalize_description(text: &str) -> Option<String> {
+    let mut paragraphs: Vec<Vec<&str>> = vec![Vec::new()];
+    for line in text.lines().map(str::trim) {
+        match paragraphs.last_mut() {
+            Some(p) if !line.is_empty() => p.extend(line.split_whitespace()),
+            Some(p) if !p.is_empty() => paragraphs.push(Vec::new()),
+            _ => {}
+        }
+    }
+    let paragraphs: Vec<String> = paragraphs
+        .into_iter()
+        .filter(|p| !p.is_empty())
+        .map(|p| p.join(" "))
+        .collect();
+    if paragraphs.is_empty() {
+        None
+    } else {
+        Some(paragraphs.join("\n\n"))
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    last_line: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.last_line, |(_, line)| *line)
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ParseError> {
+        Err(ParseError {
+            line: self.line(),
+            message: message.into(),
+        })
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T, ParseError> {
+        match self.peek() {
+            Some(t) => self.error(format!("expected {}, found {}", expected, t)),
+            None => self.error(format!(
+                "expected {}, found the end of the schema",
+                expected
+            )),
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(&Token::Punct(c)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            self.unexpected(&format!("`{}`", c))
+        }
+    }
+
+    fn ident(&mut self, expected: &str) -> Result<String, ParseError> {
+        match self.peek() {
+            Some(Token::Ident(s)) => {
+                let s = s.clone();
+                self.pos += 1;
+                Ok(s)
+            }
+            _ => self.unexpected(expected),
+        }
+    }
+
+    /// Consumes any descriptions in front of a definition, keeping the last one
+    fn description(&mut self) -> Option<String> {
+        let mut description = None;
+        while let Some(Token::Str(s)) = self.peek() {
+            description = normalize_description(s);
+            self.pos += 1;
+        }
+        description
+    }
+
+    fn document(mut self) -> Result<Document, ParseError> {
+        let mut doc = Document::default();
+        let mut namespace = false;
+        loop {
+            let description = self.description();
+            let keyword = match self.peek() {
+                None => break,
+                Some(Token::Ident(k)) => k.clone(),
+                Some(_) => return self.unexpected("a definition"),
+            };
+            match keyword.as_str() {
+                "namespace" => {
+                    if namespace {
+                        return self.error("the namespace is declared more than once");
+                    }
+                    self.pos += 1;
+                    match self.next() {
+                        Some(Token::Str(s)) => doc.namespace = s,
+                        _ => {
+                            self.pos -= 1;
+                            return self.unexpected("the namespace in quotes");
+                        }
+                    }
+                    namespace = true;
+                }
+                "role" => {
+                    self.pos += 1;
+                    let role = self.role(description)?;
+                    if doc.role(&role.name).is_some() {
+                        return self.error(format!("role `{}` is declared twice", role.name));
+                    }
+                    doc.roles.push(role);
+                }
+                "type" => {
+                    self.pos += 1;
+                    let ty = self.type_definition(description)?;
+                    if doc.type_definition(&ty.name).is_some() {
+                        return self.error(format!("type `{}` is declared twice", ty.name));
+                    }
+                    doc.types.push(ty);
+                }
+                other => {
+                    return self.error(format!("unsupported definition `{}`", other));
+                }
+            }
+        }
+        if !namespace {
+            return self.error("the schema does not declare a namespace");
+        }
+        Ok(doc)
+    }
+
+    fn role(&mut self, description: Option<String>) -> Result<Role, ParseError> {
+        let name = self.ident("the name of the role")?;
+        self.expect('{')?;
+        let mut operations: Vec<Operation> = Vec::new();
+        loop {
+            let description = self.description();
+            if self.eat('}') {
+                break;
+            }
+            let op = self.operation(description)?;
+            if operations.iter().any(|o| o.name == op.name) {
+                return self.error(format!("operation `{}` is declared twice", op.name));
+            }
+            operations.push(op);
+        }
+        Ok(Role {
+            name,
+            description,
+            operations,
+        })
+    }
+
+    fn operation(&mut self, description: Option<String>) -> Result<Operation, ParseError> {
+        let name = self.ident("an operation")?;
+        let (parameters, unary) = if self.eat('(') {
+            (self.fields(')')?, false)
+        } else if self.eat('{') {
+            let parameters = self.fields('}')?;
+            if parameters.len() != 1 {
+                return self.error(format!(
+                    "operation `{}` is declared with braces, so it must have exactly one parameter",
+                    name
+                ));
+            }
+            (parameters, true)
+        } else {
+            return self.unexpected("`(` or `{`");
+        };
+        self.expect(':')?;
+        let returns = self.ty()?;
+        Ok(Operation {
+            name,
+            description,
+            parameters,
+            unary,
+            returns,
+        })
+    }
+
+    fn type_definition(
+        &mut self,
+        description: Option<String>,
+    ) -> Result<TypeDefinition, ParseError> {
+        let name = self.ident("the name of the type")?;
+        self.expect('{')?;
+        let fields = self.fields('}')?;
+        Ok(TypeDefinition {
+            name,
+            description,
+            fields,
+        })
+    }
+
+    /// Parses fields up to and including the closing delimiter
+    fn fields(&mut self, close: char) -> Result<Vec<Field>, ParseError> {
+        let mut fields: Vec<Field> = Vec::new();
+        loop {
+            let description = self.description();
+            if self.eat(close) {
+                return Ok(fields);
+            }
+            let name = self.ident("a field")?;
+            self.expect(':')?;
+            let ty = self.ty()?;
+            if ty == Type::Void {
+                return self.error(format!("field `{}` cannot be void", name));
+            }
+            if fields.iter().any(|f| f.name == name) {
+                return self.error(format!("field `{}` is declared twice", name));
+            }
+            fields.push(Field {
+                name,
+                description,
+                ty,
+            });
+            self.eat(',');
+        }
+    }
+
+    fn ty(&mut self) -> Result<Type, ParseError> {
+        let ty = if self.eat('[') {
+            let item = self.ty()?;
+            self.expect(']')?;
+            Type::List(Box::new(item))
+        } else if self.eat('{') {
+            let key = self.ty()?;
+            self.expect(':')?;
+            let value = self.ty()?;
+            self.expect('}')?;
+            Type::Map(Box::new(key), Box::new(value))
+        } else {
+            match self.ident("a type")?.as_str() {
+                "void" => return Ok(Type::Void),
+                name => Type::Named(name.to_string()),
+            }
+        };
+        if self.eat('?') {
+            Ok(Type::Optional(Box::new(ty)))
+        } else {
+            Ok(ty)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+namespace "wasmcloud:example"
+
+# Comments are ignored
+role Store {
+    """
+    Gets a value.
+
+    Returns nothing if the key
+    doesn't exist
+    """
+    Get(key: string, limit: u32?): GetResponse
+    Watch{request: WatchRequest}: void
+    Ping(): bool
+}
+
+"""
+Response type for Get operations
+"""
+type GetResponse {
+    "The values, if any"
+    values: [string]?,
+    meta: {string: [u64]}
+}
+
+type WatchRequest {}
+"#;
+
+    #[test]
+    fn parses_schemas() {
+        let doc = parse(SCHEMA).unwrap();
+        assert_eq!(doc.namespace, "wasmcloud:example");
+
+        let store = doc.role("Store").unwrap();
+        let get = &store.operations[0];
+        assert_eq!(
+            get.description.as_deref(),
+            Some("Gets a value.\n\nReturns nothing if the key doesn't exist")
+        );
+        assert!(!get.unary);
+        assert_eq!(get.parameters[0].name, "key");
+        assert_eq!(
+            get.parameters[1].ty,
+            Type::Optional(Box::new(Type::Named("u32".to_string())))
+        );
+        assert_eq!(get.returns, Type::Named("GetResponse".to_string()));
+        assert!(store.operations[1].unary);
+        assert_eq!(store.operations[1].returns, Type::Void);
+        assert!(store.operations[2].parameters.is_empty());
+
+        let response = doc.type_definition("GetResponse").unwrap();
+        assert_eq!(
+            response.description.as_deref(),
+            Some("Response type for Get operations")
+        );
+        assert_eq!(
+            response.fields[0].description.as_deref(),
+            Some("The values, if any")
+        );
+        assert_eq!(
+            response.fields[1].ty,
+            Type::Map(
+                Box::new(Type::Named("string".to_string())),
+                Box::new(Type::List(Box::new(Type::Named("u64".to_string()))))
+            )
+        );
+        assert!(doc
+            .type_definition("WatchRequest")
+            .unwrap()
+            .fields
+            .is_empty());
+    }
+
+    #[test]
+    fn reports_errors_with_lines() {
+        let err = parse("namespace \"a\"\n\ntype A {\n  b string\n}").unwrap_err();
+        assert_eq!(err.to_string(), "line 4: expected `:`, found `string`");
+
+        let err = parse("namespace \"a\"\nenum A { B }").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: unsupported definition `enum`");
+
+        let err = parse("role A {\n  B(): void\n}").unwrap_err();
+        assert_eq!(err.message, "the schema does not declare a namespace");
+
+        let err = parse("namespace \"a\"\nrole A {\n  B{c: u8, d: u8}: void\n}").unwrap_err();
+        assert!(err.message.contains("exactly one parameter"));
+    }
+}
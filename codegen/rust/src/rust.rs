@@ -0,0 +1,1081 @@
+use crate::ast::{Document, Field, Operation, Role, Type, TypeDefinition, SCALARS};
+use crate::Generator;
+use std::fmt::Write;
+
+const GUEST: &str = "#[cfg(feature = \"guest\")]";
+
+/// Width of the text of generated doc comments
+const DOC_WIDTH: usize = 80;
+
+const KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "yield",
+];
+
+/// Converts a WIDL name such as `ListItemDelete` or `replyTo` to snake case
+pub(crate) fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = matches!(chars.get(i + 1), Some(n) if n.is_lowercase());
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower) {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+/// The Rust identifier for a field or parameter
+fn ident(name: &str) -> String {
+    let name = to_snake_case(name);
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+fn rust_type(ty: &Type) -> String {
+    match ty {
+        Type::Named(name) => match name.as_str() {
+            "string" => "String".to_string(),
+            "bytes" => "Vec<u8>".to_string(),
+            name => name.to_string(),
+        },
+        Type::List(item) => format!("Vec<{}>", rust_type(item)),
+        Type::Map(key, value) => format!(
+            "std::collections::HashMap<{}, {}>",
+            rust_type(key),
+            rust_type(value)
+        ),
+        Type::Optional(inner) => format!("Option<{}>", rust_type(inner)),
+        Type::Void => "()".to_string(),
+    }
+}
+
+fn is_bytes(ty: &Type) -> bool {
+    *ty == Type::Named("bytes".to_string())
+}
+
+/// Checks that every type referenced by a role or type is a scalar or declared in the schema
+pub(crate) fn validate(doc: &Document, roles: &[String]) -> Result<(), String> {
+    fn check(doc: &Document, ty: &Type, context: &str) -> Result<(), String> {
+        match ty {
+            Type::Named(name) => {
+                if SCALARS.contains(&name.as_str()) || doc.type_definition(name).is_some() {
+                    Ok(())
+                } else {
+                    Err(format!("{} refers to unknown type `{}`", context, name))
+                }
+            }
+            Type::List(inner) | Type::Optional(inner) => check(doc, inner, context),
+            Type::Map(key, value) => {
+                check(doc, key, context)?;
+                check(doc, value, context)
+            }
+            Type::Void => Ok(()),
+        }
+    }
+    for name in roles {
+        let role = doc
+            .role(name)
+            .ok_or_else(|| format!("the schema does not declare role `{}`", name))?;
+        for op in role.operations.iter() {
+            let context = format!("operation `{}`", op.name);
+            for param in op.parameters.iter() {
+                check(doc, &param.ty, &context)?;
+            }
+            check(doc, &op.returns, &context)?;
+        }
+    }
+    for ty in doc.types.iter() {
+        for field in ty.fields.iter() {
+            check(
+                doc,
+                &field.ty,
+                &format!("field `{}.{}`", ty.name, field.name),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a description as a doc comment, wrapping its paragraphs
+fn doc_comment(out: &mut String, indent: &str, description: &Option<String>) {
+    let description = match description {
+        Some(d) => d,
+        None => return,
+    };
+    for (i, paragraph) in description.split("\n\n").enumerate() {
+        if i > 0 {
+            writeln!(out, "{}///", indent).unwrap();
+        }
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            if !line.is_empty() && line.len() + 1 + word.len() > DOC_WIDTH {
+                writeln!(out, "{}/// {}", indent, line).unwrap();
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        writeln!(out, "{}/// {}", indent, line).unwrap();
+    }
+}
+
+fn args_struct_name(op: &Operation) -> String {
+    format!("{}Args", op.name)
+}
+
+/// Whether an arguments struct is generated for the operation
+fn has_args_struct(op: &Operation) -> bool {
+    !op.unary && !op.parameters.is_empty()
+}
+
+fn write_struct(out: &mut String, name: &str, description: &Option<String>, fields: &[Field]) {
+    doc_comment(out, "", description);
+    writeln!(
+        out,
+        "#[derive(Debug, PartialEq, Deserialize, Serialize, Default, Clone)]"
+    )
+    .unwrap();
+    writeln!(out, "pub struct {} {{", name).unwrap();
+    for field in fields {
+        doc_comment(out, "    ", &field.description);
+        if is_bytes(&field.ty) {
+            writeln!(out, "    #[serde(with = \"serde_bytes\")]").unwrap();
+        }
+        writeln!(out, "    #[serde(rename = \"{}\")]", field.name).unwrap();
+        writeln!(
+            out,
+            "    pub {}: {},",
+            ident(&field.name),
+            rust_type(&field.ty)
+        )
+        .unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+}
+
+fn borrowed_struct_name(name: &str) -> String {
+    format!("{}Ref", name)
+}
+
+/// The type of a field of a borrowed struct, and whether it borrows from the payload. Strings
+/// become `Cow`s, and bytes become slices when they are the whole field
+fn borrowed_rust_type(ty: &Type, whole_field: bool) -> (String, bool) {
+    match ty {
+        Type::Named(name) if name == "string" => ("std::borrow::Cow<'a, str>".to_string(), true),
+        Type::Named(name) if name == "bytes" && whole_field => ("&'a [u8]".to_string(), true),
+        Type::List(item) => match borrowed_rust_type(item, false) {
+            (item, true) => (format!("Vec<{}>", item), true),
+            _ => (rust_type(ty), false),
+        },
+        Type::Map(key, value) => {
+            match (
+                borrowed_rust_type(key, false),
+                borrowed_rust_type(value, false),
+            ) {
+                ((key, k), (value, v)) if k || v => (
+                    format!("std::collections::HashMap<{}, {}>", key, value),
+                    true,
+                ),
+                _ => (rust_type(ty), false),
+            }
+        }
+        Type::Optional(inner) => match borrowed_rust_type(inner, false) {
+            (inner, true) => (format!("Option<{}>", inner), true),
+            _ => (rust_type(ty), false),
+        },
+        _ => (rust_type(ty), false),
+    }
+}
+
+/// Whether a value of the type is copied rather than cloned
+fn is_copy(ty: &Type) -> bool {
+    matches!(ty, Type::Named(name) if name != "string" && name != "bytes" && SCALARS.contains(&name.as_str()))
+}
+
+/// The expression that copies `expr`, a value of the borrowed variant of `ty`, into an owned one
+fn into_owned_expr(ty: &Type, expr: &str, whole_field: bool) -> String {
+    if !borrowed_rust_type(ty, whole_field).1 {
+        return expr.to_string();
+    }
+    match ty {
+        Type::Named(name) if name == "string" => format!("{}.into_owned()", expr),
+        Type::Named(_) => format!("{}.to_vec()", expr),
+        Type::List(item) => format!(
+            "{}.into_iter().map(|v| {}).collect()",
+            expr,
+            into_owned_expr(item, "v", false)
+        ),
+        Type::Map(key, value) => format!(
+            "{}.into_iter().map(|(k, v)| ({}, {})).collect()",
+            expr,
+            into_owned_expr(key, "k", false),
+            into_owned_expr(value, "v", false)
+        ),
+        Type::Optional(inner) => {
+            format!("{}.map(|v| {})", expr, into_owned_expr(inner, "v", false))
+        }
+        Type::Void => expr.to_string(),
+    }
+}
+
+/// The expression that borrows `expr`, a value of `ty` or a reference to one if `by_ref` is set,
+/// as a value of its borrowed variant
+fn borrow_expr(ty: &Type, expr: &str, by_ref: bool, whole_field: bool) -> String {
+    if !borrowed_rust_type(ty, whole_field).1 {
+        return if !is_copy(ty) {
+            format!("{}.clone()", expr)
+        } else if by_ref {
+            format!("*{}", expr)
+        } else {
+            expr.to_string()
+        };
+    }
+    match ty {
+        Type::Named(name) if name == "string" => {
+            format!("std::borrow::Cow::Borrowed({}.as_str())", expr)
+        }
+        Type::Named(_) => format!("{}.as_slice()", expr),
+        Type::List(item) => format!(
+            "{}.iter().map(|v| {}).collect()",
+            expr,
+            borrow_expr(item, "v", true, false)
+        ),
+        Type::Map(key, value) => format!(
+            "{}.iter().map(|(k, v)| ({}, {})).collect()",
+            expr,
+            borrow_expr(key, "k", true, false),
+            borrow_expr(value, "v", true, false)
+        ),
+        Type::Optional(inner) => format!(
+            "{}.as_ref().map(|v| {})",
+            expr,
+            borrow_expr(inner, "v", true, false)
+        ),
+        Type::Void => expr.to_string(),
+    }
+}
+
+/// Writes the borrowed variant of a type and, unless the type is custom, its conversions
+fn write_borrowed_struct(out: &mut String, gen: &Generator, ty: &TypeDefinition) {
+    let name = borrowed_struct_name(&ty.name);
+    writeln!(
+        out,
+        "/// A borrowed variant of [{ty}](struct.{ty}.html). The bytes and, where possible, the\n\
+         /// strings of a `{name}` point into the payload delivered by the host instead of being\n\
+         /// copied out of it, which keeps memory usage down when receiving large payloads.",
+        ty = ty.name,
+        name = name
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "#[derive(Debug, PartialEq, Deserialize, Serialize, Default, Clone)]"
+    )
+    .unwrap();
+    writeln!(out, "pub struct {}<'a> {{", name).unwrap();
+    for field in ty.fields.iter() {
+        doc_comment(out, "    ", &field.description);
+        let (rust_ty, borrows) = borrowed_rust_type(&field.ty, true);
+        if is_bytes(&field.ty) {
+            writeln!(out, "    #[serde(with = \"serde_bytes\")]").unwrap();
+        }
+        writeln!(out, "    #[serde(rename = \"{}\")]", field.name).unwrap();
+        if borrows && !is_bytes(&field.ty) {
+            let default = if let Type::Optional(_) = field.ty {
+                ", default"
+            } else {
+                ""
+            };
+            writeln!(out, "    #[serde(borrow{})]", default).unwrap();
+        }
+        writeln!(out, "    pub {}: {},", ident(&field.name), rust_ty).unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    if gen.custom_types.contains(&ty.name) {
+        return;
+    }
+    writeln!(out, "impl<'a> {}<'a> {{", name).unwrap();
+    writeln!(
+        out,
+        "    /// Copies the value into an owned [{ty}](struct.{ty}.html)\n    \
+         pub fn into_owned(self) -> {ty} {{\n        {ty} {{",
+        ty = ty.name
+    )
+    .unwrap();
+    for field in ty.fields.iter() {
+        let field_ident = ident(&field.name);
+        writeln!(
+            out,
+            "            {}: {},",
+            field_ident,
+            into_owned_expr(&field.ty, &format!("self.{}", field_ident), true)
+        )
+        .unwrap();
+    }
+    writeln!(out, "        }}\n    }}\n}}\n").unwrap();
+
+    writeln!(
+        out,
+        "impl<'a> From<&'a {ty}> for {name}<'a> {{\n    \
+         fn from(value: &'a {ty}) -> Self {{\n        {name} {{",
+        ty = ty.name,
+        name = name
+    )
+    .unwrap();
+    for field in ty.fields.iter() {
+        let field_ident = ident(&field.name);
+        writeln!(
+            out,
+            "            {}: {},",
+            field_ident,
+            borrow_expr(&field.ty, &format!("value.{}", field_ident), false, true)
+        )
+        .unwrap();
+    }
+    writeln!(out, "        }}\n    }}\n}}\n").unwrap();
+}
+
+/// The name of the borrowed type received by a handler operation, if its single argument is one
+fn borrowed_param<'a>(gen: &Generator, op: &'a Operation) -> Option<&'a str> {
+    match &op.parameters[..] {
+        [param] => match &param.ty {
+            Type::Named(name) if gen.borrowed_types.contains(name) => Some(name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The `Fn` bound of a handler for the operation, e.g. `Fn(String) -> HandlerResult<()>`
+fn handler_signature(op: &Operation) -> String {
+    let params: Vec<String> = op.parameters.iter().map(|p| rust_type(&p.ty)).collect();
+    format!(
+        "Fn({}) -> HandlerResult<{}>",
+        params.join(", "),
+        rust_type(&op.returns)
+    )
+}
+
+/// The `Fn` bound of a handler for the operation that receives the borrowed variant of its
+/// argument
+fn borrowed_handler_signature(op: &Operation, ty: &str) -> String {
+    format!(
+        "Fn({}) -> HandlerResult<{}>",
+        borrowed_struct_name(ty),
+        rust_type(&op.returns)
+    )
+}
+
+pub(crate) fn generate(gen: &Generator, doc: &Document) -> String {
+    let core = &gen.core_crate;
+    let host_ops: Vec<&Operation> = gen
+        .host_roles
+        .iter()
+        .filter_map(|r| doc.role(r))
+        .flat_map(|r| r.operations.iter())
+        .collect();
+    let handler_ops: Vec<&Operation> = gen
+        .handler_roles
+        .iter()
+        .filter_map(|r| doc.role(r))
+        .flat_map(|r| r.operations.iter())
+        .collect();
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// Generated by wasmcloud-widl-codegen from the `{}` schema. Do not edit.\n",
+        doc.namespace
+    )
+    .unwrap();
+    writeln!(out, "use serde::{{Deserialize, Serialize}};\n").unwrap();
+    writeln!(out, "{}\nextern crate wapc_guest as guest;", GUEST).unwrap();
+    writeln!(out, "{}\nuse guest::prelude::*;", GUEST).unwrap();
+    if !host_ops.is_empty() {
+        writeln!(out, "{}\nuse {}::host_call;", GUEST, core).unwrap();
+    }
+    if gen.custom_host {
+        writeln!(out, "{}\nuse crate::Host;", GUEST).unwrap();
+    }
+    if !handler_ops.is_empty() {
+        writeln!(out, "{}\nuse {}::register_function;", GUEST, core).unwrap();
+    }
+    for ty in gen.custom_types.iter() {
+        writeln!(out, "#[allow(unused_imports)]\npub use crate::{};", ty).unwrap();
+    }
+    writeln!(out).unwrap();
+
+    if !gen.host_roles.is_empty() {
+        write_host(&mut out, gen, doc, &host_ops);
+    }
+    if !gen.handler_roles.is_empty() {
+        write_handlers(&mut out, gen, &handler_ops);
+    }
+    if let Some((role, trait_name)) = &gen.provider {
+        if let Some(role) = doc.role(role) {
+            write_provider(&mut out, gen, doc, role, trait_name);
+        }
+    }
+
+    write_contract(&mut out, gen, doc);
+
+    for op in host_ops.iter().chain(handler_ops.iter()) {
+        if has_args_struct(op) {
+            write_struct(&mut out, &args_struct_name(op), &None, &op.parameters);
+        }
+    }
+    for ty in doc
+        .types
+        .iter()
+        .filter(|t| !gen.custom_types.contains(&t.name))
+    {
+        write_struct(&mut out, &ty.name, &ty.description, &ty.fields);
+    }
+    for ty in doc
+        .types
+        .iter()
+        .filter(|t| gen.borrowed_types.contains(&t.name))
+    {
+        write_borrowed_struct(&mut out, gen, ty);
+    }
+
+    write!(
+        out,
+        r#"/// The standard function for serializing codec structs into a format that can be
+/// used for message exchange between actor and host. Use of any other function to
+/// serialize could result in breaking incompatibilities.
+pub fn serialize<T>(
+    item: T,
+) -> ::std::result::Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>
+where
+    T: Serialize,
+{{
+    {core}::codec::serialize(item)
+}}
+
+/// The standard function for de-serializing codec structs from a format suitable
+/// for message exchange between actor and host. Use of any other function to
+/// deserialize could result in breaking incompatibilities.
+pub fn deserialize<'de, T: Deserialize<'de>>(
+    buf: &[u8],
+) -> ::std::result::Result<T, Box<dyn std::error::Error + Send + Sync>> {{
+    {core}::codec::deserialize(buf)
+}}
+"#,
+        core = core
+    )
+    .unwrap();
+    out
+}
+
+fn write_host(out: &mut String, gen: &Generator, doc: &Document, ops: &[&Operation]) {
+    if !gen.custom_host {
+        write!(
+            out,
+            r#"{guest}
+pub struct Host {{
+    binding: String,
+}}
+
+{guest}
+impl Default for Host {{
+    fn default() -> Self {{
+        Host {{
+            binding: "default".to_string(),
+        }}
+    }}
+}}
+
+/// Creates a named host binding
+{guest}
+pub fn host(binding: &str) -> Host {{
+    Host {{
+        binding: binding.to_string(),
+    }}
+}}
+
+/// Creates the default host binding
+{guest}
+pub fn default() -> Host {{
+    Host::default()
+}}
+
+"#,
+            guest = GUEST
+        )
+        .unwrap();
+    }
+    writeln!(out, "{}\nimpl Host {{", GUEST).unwrap();
+    let binding = if gen.custom_host {
+        "&self.binding()"
+    } else {
+        "&self.binding"
+    };
+    for op in ops {
+        doc_comment(out, "    ", &op.description);
+        let (vis, name) = if gen.internal_operations.contains(&op.name) {
+            ("pub(crate)", format!("_{}", to_snake_case(&op.name)))
+        } else {
+            ("pub", to_snake_case(&op.name))
+        };
+        let params: Vec<String> = op
+            .parameters
+            .iter()
+            .map(|p| format!(", {}: {}", ident(&p.name), rust_type(&p.ty)))
+            .collect();
+        writeln!(
+            out,
+            "    {} fn {}(&self{}) -> HandlerResult<{}> {{",
+            vis,
+            name,
+            params.concat(),
+            rust_type(&op.returns)
+        )
+        .unwrap();
+        let traced = gen.traced_operations.contains(&op.name);
+        let payload = if op.unary {
+            let arg = ident(&op.parameters[0].name);
+            if traced {
+                writeln!(out, "        let mut {arg} = {arg};", arg = arg).unwrap();
+                writeln!(
+                    out,
+                    "        {}::trace::inject(&mut {});",
+                    gen.core_crate, arg
+                )
+                .unwrap();
+            }
+            format!("&serialize({})?", arg)
+        } else if op.parameters.is_empty() {
+            "&[]".to_string()
+        } else {
+            let fields: Vec<String> = op.parameters.iter().map(|p| ident(&p.name)).collect();
+            writeln!(
+                out,
+                "        let {}input_args = {} {{ {} }};",
+                if traced { "mut " } else { "" },
+                args_struct_name(op),
+                fields.join(", ")
+            )
+            .unwrap();
+            if traced {
+                writeln!(
+                    out,
+                    "        {}::trace::inject(&mut input_args);",
+                    gen.core_crate
+                )
+                .unwrap();
+            }
+            "&serialize(input_args)?".to_string()
+        };
+        let call = format!(
+            "host_call({}, \"{}\", \"{}\", {})",
+            binding, doc.namespace, op.name, payload
+        );
+        if op.returns == Type::Void {
+            writeln!(out, "        {}.map(|_vec| ())", call).unwrap();
+        } else {
+            let ty = rust_type(&op.returns);
+            writeln!(
+                out,
+                "        let reply = {call}?;\n        \
+                 deserialize::<{ty}>(&reply).map_err(|e| {{\n            \
+                 {core}::ActorError::internal(format!(\n                \
+                 \"failed to decode the reply to `{op}` from `{ns}` as `{ty}`: {{}}\",\n                \
+                 e\n            ))\n            .into()\n        }})",
+                call = call,
+                ty = ty,
+                core = gen.core_crate,
+                op = op.name,
+                ns = doc.namespace
+            )
+            .unwrap();
+        }
+        writeln!(out, "    }}").unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+}
+
+fn write_provider(
+    out: &mut String,
+    gen: &Generator,
+    doc: &Document,
+    role: &Role,
+    trait_name: &str,
+) {
+    const PROVIDER: &str = "#[cfg(feature = \"provider\")]";
+    const RESULT: &str = "Result<{}, Box<dyn std::error::Error + Send + Sync>>";
+    let result = |ty: &str| RESULT.replace("{}", ty);
+
+    writeln!(
+        out,
+        "/// Implemented by capability providers of `{}`, with one method per operation of the\n\
+         /// `{}` role. The first argument of every method is the public key of the calling actor.\n\
+         /// Use [dispatch](fn.dispatch.html) to route the calls of actors to an implementation\n\
+         {}\npub trait {} {{",
+        doc.namespace, role.name, PROVIDER, trait_name
+    )
+    .unwrap();
+    for (i, op) in role.operations.iter().enumerate() {
+        if i > 0 {
+            writeln!(out).unwrap();
+        }
+        doc_comment(out, "    ", &op.description);
+        let params: Vec<String> = op
+            .parameters
+            .iter()
+            .map(|p| format!(", {}: {}", ident(&p.name), rust_type(&p.ty)))
+            .collect();
+        writeln!(
+            out,
+            "    fn {}(&self, actor: &str{}) -> {};",
+            to_snake_case(&op.name),
+            params.concat(),
+            result(&rust_type(&op.returns))
+        )
+        .unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    writeln!(
+        out,
+        "/// Decodes the arguments of an operation invoked by an actor, calls the matching method of\n\
+         /// the provider and encodes its reply\n\
+         {}\npub fn dispatch<T: {} + ?Sized>(\n    provider: &T,\n    actor: &str,\n    \
+         op: &str,\n    payload: &[u8],\n) -> {} {{\n    match op {{",
+        PROVIDER,
+        trait_name,
+        result("Vec<u8>")
+    )
+    .unwrap();
+    for op in role.operations.iter() {
+        writeln!(out, "        \"{}\" => {{", op.name).unwrap();
+        let args = if op.unary {
+            writeln!(
+                out,
+                "            let input = deserialize::<{}>(payload)?;",
+                rust_type(&op.parameters[0].ty)
+            )
+            .unwrap();
+            ", input".to_string()
+        } else if op.parameters.is_empty() {
+            String::new()
+        } else {
+            writeln!(
+                out,
+                "            let input = deserialize::<{}>(payload)?;",
+                args_struct_name(op)
+            )
+            .unwrap();
+            let args: Vec<String> = op
+                .parameters
+                .iter()
+                .map(|p| format!(", input.{}", ident(&p.name)))
+                .collect();
+            args.concat()
+        };
+        let call = format!("provider.{}(actor{})?", to_snake_case(&op.name), args);
+        if op.returns == Type::Void {
+            writeln!(out, "            {};\n            Ok(Vec::new())", call).unwrap();
+        } else {
+            writeln!(out, "            serialize({})", call).unwrap();
+        }
+        writeln!(out, "        }}").unwrap();
+    }
+    writeln!(
+        out,
+        "        _ => Err({}::ActorError::invalid_argument(format!(\n            \
+         \"unknown operation `{{}}` for {}\",\n            op\n        ))\n        .into()),\n    }}\n}}\n",
+        gen.core_crate, doc.namespace
+    )
+    .unwrap();
+}
+
+fn write_handlers(out: &mut String, gen: &Generator, ops: &[&Operation]) {
+    let core = &gen.core_crate;
+    writeln!(out, "{}\npub struct Handlers {{}}\n", GUEST).unwrap();
+    writeln!(out, "{}\nimpl Handlers {{", GUEST).unwrap();
+    for op in ops {
+        let name = to_snake_case(&op.name);
+        doc_comment(out, "    ", &op.description);
+        writeln!(
+            out,
+            "    pub fn register_{}(f: impl {} + Send + Sync + 'static) {{",
+            name,
+            handler_signature(op)
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "        *{}.write().unwrap() = Some(std::sync::Arc::new(f));",
+            name.to_uppercase()
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "        register_function(\"{}\", {}_wrapper);",
+            op.name, name
+        )
+        .unwrap();
+        writeln!(out, "    }}").unwrap();
+        if let Some(ty) = borrowed_param(gen, op) {
+            writeln!(
+                out,
+                "    /// Registers a handler for `{op}` that receives a borrowed\n    \
+                 /// [{ref_ty}](struct.{ref_ty}.html) instead of a `{ty}`. This replaces any handler\n    \
+                 /// registered with `register_{name}`, and vice versa.\n    \
+                 pub fn register_{name}_ref(f: impl {sig} + Send + Sync + 'static) {{\n        \
+                 *{upper}_REF.write().unwrap() = Some(std::sync::Arc::new(f));\n        \
+                 register_function(\"{op}\", {name}_ref_wrapper);\n    }}",
+                op = op.name,
+                ty = ty,
+                ref_ty = borrowed_struct_name(ty),
+                name = name,
+                upper = name.to_uppercase(),
+                sig = borrowed_handler_signature(op, ty)
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    for op in ops {
+        writeln!(
+            out,
+            "{}\ntype {}Handler = std::sync::Arc<dyn {} + Send + Sync>;\n",
+            GUEST,
+            op.name,
+            handler_signature(op)
+        )
+        .unwrap();
+        if let Some(ty) = borrowed_param(gen, op) {
+            writeln!(
+                out,
+                "{}\ntype {}RefHandler = std::sync::Arc<dyn {} + Send + Sync>;\n",
+                GUEST,
+                op.name,
+                borrowed_handler_signature(op, ty)
+            )
+            .unwrap();
+        }
+    }
+
+    writeln!(out, "{}\nlazy_static::lazy_static! {{", GUEST).unwrap();
+    for op in ops {
+        writeln!(
+            out,
+            "    static ref {}: std::sync::RwLock<Option<{}Handler>> = std::sync::RwLock::new(None);",
+            to_snake_case(&op.name).to_uppercase(),
+            op.name
+        )
+        .unwrap();
+        if borrowed_param(gen, op).is_some() {
+            writeln!(
+                out,
+                "    static ref {}_REF: std::sync::RwLock<Option<{}RefHandler>> = std::sync::RwLock::new(None);",
+                to_snake_case(&op.name).to_uppercase(),
+                op.name
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    for op in ops {
+        let name = to_snake_case(&op.name);
+        let (input, args) = if op.parameters.is_empty() {
+            ("_input_payload", String::new())
+        } else if op.unary {
+            ("input_payload", "input".to_string())
+        } else {
+            let args: Vec<String> = op
+                .parameters
+                .iter()
+                .map(|p| format!("input.{}", ident(&p.name)))
+                .collect();
+            ("input_payload", args.join(", "))
+        };
+        writeln!(
+            out,
+            "{}\nfn {}_wrapper(input_payload: &[u8]) -> CallResult {{",
+            GUEST, name
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "    {}::interceptor::invocation(\"{}\", input_payload, |{}| {{",
+            core, op.name, input
+        )
+        .unwrap();
+        if op.unary {
+            writeln!(
+                out,
+                "        let input = deserialize::<{}>(input_payload)?;",
+                rust_type(&op.parameters[0].ty)
+            )
+            .unwrap();
+        } else if !op.parameters.is_empty() {
+            writeln!(
+                out,
+                "        let input = deserialize::<{}>(input_payload)?;",
+                args_struct_name(op)
+            )
+            .unwrap();
+        }
+        if gen.traced_operations.contains(&op.name) {
+            writeln!(out, "        {}::trace::extract(&input);", core).unwrap();
+        }
+        writeln!(
+            out,
+            "        let f = {}.read().unwrap().clone().unwrap();",
+            name.to_uppercase()
+        )
+        .unwrap();
+        if op.returns == Type::Void {
+            writeln!(out, "        f({})?;", args).unwrap();
+            writeln!(out, "        serialize(())").unwrap();
+        } else {
+            writeln!(out, "        let result = f({})?;", args).unwrap();
+            writeln!(out, "        serialize(result)").unwrap();
+        }
+        writeln!(out, "    }})\n}}\n").unwrap();
+
+        if let Some(ty) = borrowed_param(gen, op) {
+            let (args_ty, arg) = if op.unary {
+                (borrowed_struct_name(ty), "input".to_string())
+            } else {
+                let param = &op.parameters[0];
+                writeln!(
+                    out,
+                    "{}\n#[derive(Deserialize)]\nstruct {}Ref<'a> {{\n    \
+                     #[serde(rename = \"{}\")]\n    #[serde(borrow)]\n    {}: {}<'a>,\n}}\n",
+                    GUEST,
+                    args_struct_name(op),
+                    param.name,
+                    ident(&param.name),
+                    borrowed_struct_name(ty)
+                )
+                .unwrap();
+                (
+                    format!("{}Ref", args_struct_name(op)),
+                    format!("input.{}", ident(&param.name)),
+                )
+            };
+            writeln!(
+                out,
+                "{}\nfn {}_ref_wrapper(input_payload: &[u8]) -> CallResult {{",
+                GUEST, name
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "    {}::interceptor::invocation(\"{}\", input_payload, |input_payload| {{",
+                core, op.name
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "        let input = {}::codec::deserialize_borrowed::<{}>(input_payload)?;",
+                core, args_ty
+            )
+            .unwrap();
+            if gen.traced_operations.contains(&op.name) {
+                writeln!(out, "        {}::trace::extract(&{});", core, arg).unwrap();
+            }
+            writeln!(
+                out,
+                "        let f = {}_REF.read().unwrap().clone().unwrap();",
+                name.to_uppercase()
+            )
+            .unwrap();
+            if op.returns == Type::Void {
+                writeln!(out, "        f({})?;\n        serialize(())", arg).unwrap();
+            } else {
+                writeln!(
+                    out,
+                    "        let result = f({})?;\n        serialize(result)",
+                    arg
+                )
+                .unwrap();
+            }
+            writeln!(out, "    }})\n}}\n").unwrap();
+        }
+    }
+}
+
+/// The expression for a `Type` of the contract descriptor
+fn contract_type(core: &str, ty: &Type) -> String {
+    match ty {
+        Type::Named(name) => format!("{}::contract::Type::Named({:?})", core, name),
+        Type::List(item) => format!(
+            "{}::contract::Type::List(&{})",
+            core,
+            contract_type(core, item)
+        ),
+        Type::Map(key, value) => format!(
+            "{}::contract::Type::Map(&{}, &{})",
+            core,
+            contract_type(core, key),
+            contract_type(core, value)
+        ),
+        Type::Optional(inner) => format!(
+            "{}::contract::Type::Optional(&{})",
+            core,
+            contract_type(core, inner)
+        ),
+        Type::Void => format!("{}::contract::Type::Void", core),
+    }
+}
+
+fn contract_description(description: &Option<String>) -> String {
+    match description {
+        Some(d) => format!("Some({:?})", d),
+        None => "None".to_string(),
+    }
+}
+
+fn write_contract_fields(out: &mut String, core: &str, indent: &str, fields: &[Field]) {
+    for field in fields {
+        writeln!(out, "{}{}::contract::Field {{", indent, core).unwrap();
+        writeln!(out, "{}    name: {:?},", indent, field.name).unwrap();
+        writeln!(
+            out,
+            "{}    description: {},",
+            indent,
+            contract_description(&field.description)
+        )
+        .unwrap();
+        writeln!(out, "{}    ty: {},", indent, contract_type(core, &field.ty)).unwrap();
+        writeln!(out, "{}}},", indent).unwrap();
+    }
+}
+
+/// Writes the `CONTRACT` descriptor of the configured roles and of every declared type
+fn write_contract(out: &mut String, gen: &Generator, doc: &Document) {
+    let core = &gen.core_crate;
+    let roles = gen
+        .host_roles
+        .iter()
+        .map(|r| (r, "ToProvider"))
+        .chain(gen.handler_roles.iter().map(|r| (r, "ToActor")));
+    writeln!(
+        out,
+        "/// Describes the `{}` contract implemented by this crate",
+        doc.namespace
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "pub static CONTRACT: {core}::contract::Contract = {core}::contract::Contract {{",
+        core = core
+    )
+    .unwrap();
+    writeln!(out, "    capability_id: {:?},", doc.namespace).unwrap();
+    writeln!(out, "    version: env!(\"CARGO_PKG_VERSION\"),").unwrap();
+    writeln!(out, "    roles: &[").unwrap();
+    for (name, direction) in roles {
+        let role = match doc.role(name) {
+            Some(role) => role,
+            None => continue,
+        };
+        writeln!(out, "        {}::contract::Role {{", core).unwrap();
+        writeln!(out, "            name: {:?},", role.name).unwrap();
+        writeln!(
+            out,
+            "            description: {},",
+            contract_description(&role.description)
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "            direction: {}::contract::Direction::{},",
+            core, direction
+        )
+        .unwrap();
+        writeln!(out, "            operations: &[").unwrap();
+        for op in role.operations.iter() {
+            writeln!(out, "                {}::contract::Operation {{", core).unwrap();
+            writeln!(out, "                    name: {:?},", op.name).unwrap();
+            writeln!(
+                out,
+                "                    description: {},",
+                contract_description(&op.description)
+            )
+            .unwrap();
+            writeln!(out, "                    unary: {},", op.unary).unwrap();
+            writeln!(out, "                    args: &[").unwrap();
+            write_contract_fields(out, core, "                        ", &op.parameters);
+            writeln!(out, "                    ],").unwrap();
+            writeln!(
+                out,
+                "                    returns: {},",
+                contract_type(core, &op.returns)
+            )
+            .unwrap();
+            writeln!(out, "                }},").unwrap();
+        }
+        writeln!(out, "            ],").unwrap();
+        writeln!(out, "        }},").unwrap();
+    }
+    writeln!(out, "    ],").unwrap();
+    writeln!(out, "    types: &[").unwrap();
+    for ty in doc.types.iter() {
+        writeln!(out, "        {}::contract::TypeSchema {{", core).unwrap();
+        writeln!(out, "            name: {:?},", ty.name).unwrap();
+        writeln!(
+            out,
+            "            description: {},",
+            contract_description(&ty.description)
+        )
+        .unwrap();
+        writeln!(out, "            fields: &[").unwrap();
+        write_contract_fields(out, core, "                ", &ty.fields);
+        writeln!(out, "            ],").unwrap();
+        writeln!(out, "        }},").unwrap();
+    }
+    writeln!(out, "    ],").unwrap();
+    writeln!(out, "}};\n").unwrap();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn converts_names_to_snake_case() {
+        assert_eq!(to_snake_case("ListItemDelete"), "list_item_delete");
+        assert_eq!(to_snake_case("replyTo"), "reply_to");
+        assert_eq!(to_snake_case("new_count"), "new_count");
+        assert_eq!(to_snake_case("RequestGUID"), "request_guid");
+        assert_eq!(to_snake_case("HTTPRequest"), "http_request");
+        assert_eq!(ident("type"), "r#type");
+    }
+
+    #[test]
+    fn wraps_doc_comments() {
+        let mut out = String::new();
+        let words = vec!["word"; 20].join(" ");
+        doc_comment(
+            &mut out,
+            "    ",
+            &Some(format!("{}\n\nSecond paragraph", words)),
+        );
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].len() <= DOC_WIDTH + 8);
+        assert_eq!(lines[2], "    ///");
+        assert_eq!(lines[3], "    /// Second paragraph");
+    }
+}
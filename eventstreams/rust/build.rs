@@ -0,0 +1,8 @@
+fn main() {
+    wasmcloud_widl_codegen::Generator::new("eventstreams.widl")
+        .host_role("Producer")
+        .provider_role("Producer", "EventStreams")
+        .handler_role("Consumer")
+        .build()
+        .unwrap();
+}
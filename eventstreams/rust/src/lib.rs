@@ -32,11 +32,56 @@
 //!    Ok(http::Response::ok())
 //! }
 //! ```
+//!
+//! The `EventAck` returned by `write_event` can be converted into a `Result` carrying either
+//! the event ID or a structured `ActorError` with `IntoActorResult::into_result`.
+//!
+//! # Capability providers
+//! The `provider` feature exposes the [EventStreams](trait.EventStreams.html) trait, which capability providers
+//! implement for writing events to and querying event streams, and a [dispatch](fn.dispatch.html) function that
+//! routes an actor's call to it.
 
 #[allow(dead_code)]
-mod generated;
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+}
 
 pub use generated::*;
 
+use wasmcloud_actor_core::{ActorError, IntoActorResult};
+
 pub const OP_WRITE_EVENT: &str = "WriteEvent";
 pub const OP_QUERY_STREAM: &str = "QueryStream";
+
+impl IntoActorResult for EventAck {
+    /// The ID of the event that was written
+    type Ok = String;
+
+    /// Recovers the kind of the error reported by the provider, if it used the format of
+    /// `ActorError`. An acknowledgement with neither an event ID nor an error is reported as
+    /// an `Internal` error
+    fn into_result(self) -> Result<String, ActorError> {
+        match (self.event_id, self.error) {
+            (_, Some(e)) => Err(ActorError::parse(&e)),
+            (Some(id), None) => Ok(id),
+            (None, None) => Err(ActorError::internal(
+                "event was acknowledged without an event ID",
+            )),
+        }
+    }
+}
+
+impl From<Result<String, ActorError>> for EventAck {
+    fn from(res: Result<String, ActorError>) -> Self {
+        match res {
+            Ok(id) => EventAck {
+                event_id: Some(id),
+                error: None,
+            },
+            Err(e) => EventAck {
+                event_id: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
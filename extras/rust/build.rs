@@ -0,0 +1,7 @@
+fn main() {
+    wasmcloud_widl_codegen::Generator::new("extras.widl")
+        .host_role("Generator")
+        .provider_role("Generator", "Extras")
+        .build()
+        .unwrap();
+}
@@ -39,8 +39,15 @@
 //! # }
 //!
 //! ```
+//!
+//! # Capability providers
+//! The `provider` feature exposes the [Extras](trait.Extras.html) trait, which capability providers implement for
+//! generating GUIDs, random numbers and sequence numbers, and a [dispatch](fn.dispatch.html) function that routes
+//! an actor's call to it.
 
-mod generated;
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+}
 #[allow(unused_imports)]
 pub use generated::*;
 
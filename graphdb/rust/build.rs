@@ -0,0 +1,8 @@
+fn main() {
+    wasmcloud_widl_codegen::Generator::new("graphdb.widl")
+        .host_role("Store")
+        .provider_role("Store", "GraphDb")
+        .internal_operation("QueryGraph")
+        .build()
+        .unwrap();
+}
@@ -39,8 +39,15 @@
 //! }
 //!
 //! ```
+//!
+//! # Capability providers
+//! The `provider` feature exposes the [GraphDb](trait.GraphDb.html) trait, which capability providers implement for
+//! running graph queries and deleting graphs, and a [dispatch](fn.dispatch.html) function that routes an actor's
+//! call to it.
 
-pub mod generated;
+pub mod generated {
+    include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+}
 #[allow(unused_imports)]
 pub use generated::*;
 #[macro_use]
@@ -73,13 +80,8 @@ impl Host {
         graph_name: String,
         query: String,
     ) -> ::wapc_guest::HandlerResult<T> {
-        let res = self
-            ._query_graph(graph_name.into(), query.into())
-            .map(|res| T::from_table(&res.result_set));
-        match res {
-            Ok(Ok(l)) => Ok(l),
-            _ => Err("Graph conversion error".into()),
-        }
+        let res = self._query_graph(graph_name, query)?;
+        T::from_table(&res.result_set).map_err(|e| format!("Graph conversion error: {}", e).into())
     }
 }
 
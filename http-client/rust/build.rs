@@ -0,0 +1,9 @@
+fn main() {
+    wasmcloud_widl_codegen::Generator::new("httpclient.widl")
+        .host_role("Client")
+        .provider_role("Client", "HttpClient")
+        .internal_operation("Request")
+        .custom_type("Response")
+        .build()
+        .unwrap();
+}
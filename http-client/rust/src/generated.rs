@@ -1,122 +0,0 @@
-extern crate rmp_serde as rmps;
-use rmps::{Deserializer, Serializer};
-use serde::{Deserialize, Serialize};
-use std::io::Cursor;
-
-#[cfg(feature = "guest")]
-extern crate wapc_guest as guest;
-#[cfg(feature = "guest")]
-use guest::prelude::*;
-
-#[cfg(feature = "guest")]
-pub struct Host {
-    binding: String,
-}
-
-#[cfg(feature = "guest")]
-impl Default for Host {
-    fn default() -> Self {
-        Host {
-            binding: "default".to_string(),
-        }
-    }
-}
-
-/// Creates a named host binding
-#[cfg(feature = "guest")]
-pub fn host(binding: &str) -> Host {
-    Host {
-        binding: binding.to_string(),
-    }
-}
-
-/// Creates the default host binding
-#[cfg(feature = "guest")]
-pub fn default() -> Host {
-    Host::default()
-}
-
-#[cfg(feature = "guest")]
-impl Host {
-    /// Perform an HTTP request with the linked http-client provider. Your actor must
-    /// have an active configured link in order to invoke this function.
-    pub fn request(
-        &self,
-        method: String,
-        url: String,
-        headers: std::collections::HashMap<String, String>,
-        body: Vec<u8>,
-    ) -> HandlerResult<Response> {
-        let input_args = RequestArgs {
-            method,
-            url,
-            headers,
-            body,
-        };
-        host_call(
-            &self.binding,
-            "wasmcloud:httpclient",
-            "Request",
-            &serialize(input_args)?,
-        )
-        .map(|vec| {
-            let resp = deserialize::<Response>(vec.as_ref()).unwrap();
-            resp
-        })
-        .map_err(|e| e.into())
-    }
-}
-
-#[derive(Debug, PartialEq, Deserialize, Serialize, Default, Clone)]
-pub struct RequestArgs {
-    #[serde(rename = "method")]
-    pub method: String,
-    #[serde(rename = "url")]
-    pub url: String,
-    #[serde(rename = "headers")]
-    pub headers: std::collections::HashMap<String, String>,
-    #[serde(with = "serde_bytes")]
-    #[serde(rename = "body")]
-    pub body: Vec<u8>,
-}
-
-/// Response object that is returned from an HTTP request
-#[derive(Debug, PartialEq, Deserialize, Serialize, Default, Clone)]
-pub struct Response {
-    #[serde(rename = "statusCode")]
-    pub status_code: u32,
-    #[serde(rename = "status")]
-    pub status: String,
-    #[serde(rename = "header")]
-    pub header: std::collections::HashMap<String, String>,
-    #[serde(with = "serde_bytes")]
-    #[serde(rename = "body")]
-    pub body: Vec<u8>,
-}
-
-/// The standard function for serializing codec structs into a format that can be
-/// used for message exchange between actor and host. Use of any other function to
-/// serialize could result in breaking incompatibilities.
-pub fn serialize<T>(
-    item: T,
-) -> ::std::result::Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>
-where
-    T: Serialize,
-{
-    let mut buf = Vec::new();
-    item.serialize(&mut Serializer::new(&mut buf).with_struct_map())?;
-    Ok(buf)
-}
-
-/// The standard function for de-serializing codec structs from a format suitable
-/// for message exchange between actor and host. Use of any other function to
-/// deserialize could result in breaking incompatibilities.
-pub fn deserialize<'de, T: Deserialize<'de>>(
-    buf: &[u8],
-) -> ::std::result::Result<T, Box<dyn std::error::Error + Send + Sync>> {
-    let mut de = Deserializer::new(Cursor::new(buf));
-    match Deserialize::deserialize(&mut de) {
-        Ok(t) => Ok(t),
-        Err(e) => Err(format!("Failed to de-serialize: {}", e).into()),
-    }
-}
@@ -39,20 +39,153 @@
 //!     }
 //! }
 //!
-//! # fn request(method: String, url: String, headers: std::collections::HashMap<String,String>, body: Vec<u8>) -> HandlerResult<httpclient::Response> {
+//! # fn request(method: String, url: String, headers: httpclient::HeaderMap, body: Vec<u8>) -> HandlerResult<httpclient::Response> {
 //! #   Ok(httpclient::Response {
 //! #     status: "OK".to_string(),
 //! #     status_code: 200,
 //! #     ..Default::default()
 //! #   })
 //! # }
+//! ```
+//!
+//! Headers are held in a case-insensitive [HeaderMap](struct.HeaderMap.html), shared with the HTTP server interface,
+//! in which a header can have several values.
+//!
+//! Every request carries the trace context of the invocation that makes it in a `traceparent` header, so the
+//! receiving service can continue the trace. See `wasmcloud_actor_core::trace`.
+//!
+//! # Capability providers
+//! The `provider` feature exposes the [HttpClient](trait.HttpClient.html) trait, which capability providers
+//! implement for making outbound HTTP requests, and a [dispatch](fn.dispatch.html) function that routes an actor's
+//! call to it.
 
 #[cfg(feature = "guest")]
 extern crate wapc_guest as guest;
 #[cfg(feature = "guest")]
 #[allow(unused)]
 use guest::prelude::*;
-mod generated;
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+}
 pub use generated::*;
 
+pub use wasmcloud_actor_core::header::HeaderMap;
+
+use serde::{Deserialize, Serialize};
+
 pub const OP_REQUEST: &str = "Request";
+
+/// Response object that is returned from an HTTP request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Default, Clone)]
+pub struct Response {
+    #[serde(rename = "statusCode")]
+    pub status_code: u32,
+    #[serde(rename = "status")]
+    pub status: String,
+    /// The headers of the response, carried in the `header` and `headerValues` fields of the
+    /// contract
+    #[serde(flatten, with = "wasmcloud_actor_core::header::fields")]
+    pub header: HeaderMap,
+    #[serde(with = "serde_bytes")]
+    #[serde(rename = "body")]
+    pub body: Vec<u8>,
+}
+
+#[cfg(feature = "guest")]
+impl Host {
+    /// Perform an HTTP request with the linked http-client provider. Your actor must have an
+    /// active configured link in order to invoke this function.
+    ///
+    /// The trace context of the current invocation is attached in a `traceparent` header,
+    /// replacing any `traceparent` header given.
+    pub fn request(
+        &self,
+        method: String,
+        url: String,
+        headers: impl Into<HeaderMap>,
+        body: Vec<u8>,
+    ) -> HandlerResult<Response> {
+        let mut headers = headers.into();
+        wasmcloud_actor_core::trace::inject(&mut headers);
+        self._request(
+            method,
+            url,
+            headers.to_joined(),
+            Some(headers.to_lists()),
+            body,
+        )
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "guest")]
+mod test {
+    use crate::{deserialize, HeaderMap, RequestArgs, Response};
+    use std::collections::HashMap;
+    use wasmcloud_actor_core::{testing, trace};
+
+    #[test]
+    fn requests_carry_the_trace_context() {
+        testing::expect(
+            "default",
+            "wasmcloud:httpclient",
+            "Request",
+            |_: RequestArgs| Ok(Response::default()),
+        );
+        let ctx = trace::TraceContext::root();
+        trace::set_current(Some(ctx));
+        let mut headers = HashMap::new();
+        headers.insert("Traceparent".to_string(), "stale".to_string());
+        crate::default()
+            .request(
+                "GET".to_string(),
+                "http://example".to_string(),
+                headers,
+                vec![],
+            )
+            .unwrap();
+        trace::set_current(None);
+
+        let args: RequestArgs = deserialize(&testing::calls()[0].payload).unwrap();
+        assert_eq!(args.headers.len(), 1);
+        assert_eq!(args.headers["Traceparent"], ctx.to_string());
+    }
+
+    #[test]
+    fn sends_and_receives_repeated_headers() {
+        testing::expect(
+            "multi",
+            "wasmcloud:httpclient",
+            "Request",
+            |args: RequestArgs| {
+                let headers = HeaderMap::from_fields(args.headers, args.header_values);
+                let mut header = HeaderMap::new();
+                for accept in headers.get_all("accept") {
+                    header.append("Set-Cookie", format!("accept={}", accept));
+                }
+                Ok(Response {
+                    header,
+                    ..Default::default()
+                })
+            },
+        );
+        let mut headers = HeaderMap::new();
+        headers.append("Accept", "text/html");
+        headers.append("Accept", "application/json");
+        let res = crate::host("multi")
+            .request(
+                "GET".to_string(),
+                "http://example".to_string(),
+                headers,
+                vec![],
+            )
+            .unwrap();
+
+        assert_eq!(
+            res.header.get_all("set-cookie"),
+            ["accept=text/html", "accept=application/json"]
+        );
+        let args: RequestArgs = deserialize(&testing::calls()[0].payload).unwrap();
+        assert_eq!(args.headers["Accept"], "text/html, application/json");
+    }
+}
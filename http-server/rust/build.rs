@@ -0,0 +1,10 @@
+fn main() {
+    wasmcloud_widl_codegen::Generator::new("httpserver.widl")
+        .handler_role("Actor")
+        .custom_type("Request")
+        .custom_type("Response")
+        .borrowed_type("Request")
+        .trace_operation("HandleRequest")
+        .build()
+        .unwrap();
+}
@@ -0,0 +1,480 @@
+//! Parsing of request bodies as JSON, `application/x-www-form-urlencoded` forms and
+//! `multipart/form-data`
+
+use crate::query;
+use crate::{HeaderMap, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::error::Error;
+use std::fmt;
+
+/// The largest body, in bytes, that [Request::json](struct.Request.html#method.json) and
+/// [Request::form](struct.Request.html#method.form) accept
+pub const DEFAULT_BODY_LIMIT: usize = 1024 * 1024;
+
+/// The largest part, in bytes, that [Multipart](struct.Multipart.html) accepts unless another
+/// limit is set with [Multipart::part_limit](struct.Multipart.html#method.part_limit)
+pub const DEFAULT_PART_LIMIT: usize = 8 * 1024 * 1024;
+
+/// The largest whole body, in bytes, that
+/// [Request::multipart](struct.Request.html#method.multipart) accepts. It is larger than the
+/// other limits, as multipart bodies usually carry file uploads
+pub const DEFAULT_MULTIPART_LIMIT: usize = 16 * 1024 * 1024;
+
+const JSON: &str = "application/json";
+const FORM: &str = "application/x-www-form-urlencoded";
+const MULTIPART: &str = "multipart/form-data";
+
+/// A request body that can't be parsed as requested. Converts into a [Response](struct.Response.html)
+/// describing the problem: `415 Unsupported Media Type`, `413 Payload Too Large` or
+/// `400 Bad Request`
+#[derive(Debug, Clone, PartialEq)]
+pub enum BodyError {
+    /// The `Content-Type` header is missing or names another media type
+    UnsupportedMediaType {
+        expected: &'static str,
+        found: Option<String>,
+    },
+    /// The body, or a part of a multipart body, is larger than the limit, in bytes
+    TooLarge { limit: usize },
+    /// The body doesn't match the type it is parsed into, or is malformed
+    Invalid(String),
+}
+
+impl fmt::Display for BodyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BodyError::UnsupportedMediaType {
+                expected,
+                found: Some(found),
+            } => write!(f, "expected a body of type `{}`, not `{}`", expected, found),
+            BodyError::UnsupportedMediaType {
+                expected,
+                found: None,
+            } => write!(f, "expected a body of type `{}`", expected),
+            BodyError::TooLarge { limit } => {
+                write!(f, "the body is larger than the limit of {} bytes", limit)
+            }
+            BodyError::Invalid(message) => write!(f, "invalid body: {}", message),
+        }
+    }
+}
+
+impl Error for BodyError {}
+
+impl From<BodyError> for Response {
+    fn from(e: BodyError) -> Response {
+        let status = match e {
+            BodyError::UnsupportedMediaType { .. } => StatusCode::UnsupportedMediaType,
+            BodyError::TooLarge { .. } => StatusCode::PayloadTooLarge,
+            BodyError::Invalid(_) => StatusCode::BadRequest,
+        };
+        Response {
+            body: e.to_string().into_bytes(),
+            ..Response::new(status)
+        }
+    }
+}
+
+pub(crate) fn json<T: DeserializeOwned>(
+    content_type: Option<&str>,
+    body: &[u8],
+    limit: usize,
+) -> Result<T, BodyError> {
+    let media_type = media_type(content_type, JSON)?;
+    if media_type != JSON && !media_type.ends_with("+json") {
+        return Err(unsupported(content_type, JSON));
+    }
+    check_limit(body, limit)?;
+    serde_json::from_slice(body).map_err(|e| BodyError::Invalid(e.to_string()))
+}
+
+pub(crate) fn form<T: DeserializeOwned>(
+    content_type: Option<&str>,
+    body: &[u8],
+    limit: usize,
+) -> Result<T, BodyError> {
+    if media_type(content_type, FORM)? != FORM {
+        return Err(unsupported(content_type, FORM));
+    }
+    check_limit(body, limit)?;
+    let body = std::str::from_utf8(body).map_err(|e| BodyError::Invalid(e.to_string()))?;
+    query::from_pairs(query::pairs(body)).map_err(|e| BodyError::Invalid(e.message))
+}
+
+pub(crate) fn multipart<'a>(
+    content_type: Option<&str>,
+    body: &'a [u8],
+    limit: usize,
+) -> Result<Multipart<'a>, BodyError> {
+    if media_type(content_type, MULTIPART)? != MULTIPART {
+        return Err(unsupported(content_type, MULTIPART));
+    }
+    check_limit(body, limit)?;
+    let boundary = content_type
+        .and_then(|value| parameter(value, "boundary"))
+        .filter(|boundary| !boundary.is_empty())
+        .ok_or_else(|| BodyError::Invalid("missing multipart boundary".to_string()))?;
+    Ok(Multipart {
+        body,
+        delimiter: format!("\r\n--{}", boundary).into_bytes(),
+        pos: None,
+        part_limit: DEFAULT_PART_LIMIT,
+        done: false,
+    })
+}
+
+/// The lowercase media type of a `Content-Type` value, without its parameters
+fn media_type(content_type: Option<&str>, expected: &'static str) -> Result<String, BodyError> {
+    match content_type {
+        Some(value) => Ok(value
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()),
+        None => Err(unsupported(None, expected)),
+    }
+}
+
+fn unsupported(content_type: Option<&str>, expected: &'static str) -> BodyError {
+    BodyError::UnsupportedMediaType {
+        expected,
+        found: content_type.map(str::to_string),
+    }
+}
+
+fn check_limit(body: &[u8], limit: usize) -> Result<(), BodyError> {
+    if body.len() > limit {
+        Err(BodyError::TooLarge { limit })
+    } else {
+        Ok(())
+    }
+}
+
+/// The value of a parameter of a header value such as `form-data; name="file"`, with quotes and
+/// escapes removed
+fn parameter(value: &str, name: &str) -> Option<String> {
+    let mut rest = value.split_once(';')?.1;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ';' || c.is_whitespace());
+        if rest.is_empty() {
+            return None;
+        }
+        let eq = rest.find('=');
+        let end = rest.find(';').unwrap_or(rest.len());
+        let (key, value, next) = match eq {
+            Some(eq) if eq < end => {
+                let (value, next) = parameter_value(&rest[eq + 1..]);
+                (rest[..eq].trim(), value, next)
+            }
+            _ => (rest[..end].trim(), String::new(), &rest[end..]),
+        };
+        if key.eq_ignore_ascii_case(name) {
+            return Some(value);
+        }
+        rest = next;
+    }
+}
+
+/// Reads a token or quoted string, returning it along with the rest of the input
+fn parameter_value(input: &str) -> (String, &str) {
+    let input = input.trim_start();
+    if !input.starts_with('"') {
+        let end = input.find(';').unwrap_or(input.len());
+        return (input[..end].trim().to_string(), &input[end..]);
+    }
+    let mut value = String::new();
+    let mut chars = input.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return (value, &input[i + 1..]),
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    value.push(escaped);
+                }
+            }
+            c => value.push(c),
+        }
+    }
+    (value, "")
+}
+
+/// The parts of a `multipart/form-data` body, read one at a time. Parts borrow their data from the
+/// body, so reading a large upload doesn't copy it.
+///
+/// Iteration stops after the first error, such as a part larger than the
+/// [part limit](#method.part_limit) or a body that ends before its closing boundary.
+#[derive(Debug, Clone)]
+pub struct Multipart<'a> {
+    body: &'a [u8],
+    delimiter: Vec<u8>,
+    /// The position just after the last delimiter read, if any
+    pos: Option<usize>,
+    part_limit: usize,
+    done: bool,
+}
+
+impl<'a> Multipart<'a> {
+    /// Sets the largest part, in bytes, that is accepted
+    pub fn part_limit(mut self, limit: usize) -> Self {
+        self.part_limit = limit;
+        self
+    }
+
+    fn next_part(&mut self) -> Result<Option<Part<'a>>, BodyError> {
+        let mut pos = match self.pos {
+            Some(pos) => pos,
+            // The first delimiter may start the body, without the line break before it
+            None if self.body.starts_with(&self.delimiter[2..]) => self.delimiter.len() - 2,
+            None => match find(self.body, &self.delimiter, 0) {
+                Some(i) => i + self.delimiter.len(),
+                None => return Err(malformed("the body has no boundary")),
+            },
+        };
+        let rest = &self.body[pos..];
+        if rest.starts_with(b"--") {
+            return Ok(None);
+        }
+        // Whitespace may follow a delimiter before the line break
+        let padding = rest
+            .iter()
+            .take_while(|b| **b == b' ' || **b == b'\t')
+            .count();
+        if !rest[padding..].starts_with(b"\r\n") {
+            return Err(malformed("a boundary is not followed by a line break"));
+        }
+        pos += padding + 2;
+
+        let headers_end = if self.body[pos..].starts_with(b"\r\n") {
+            pos
+        } else {
+            find(self.body, b"\r\n\r\n", pos)
+                .ok_or_else(|| malformed("the headers of a part are not terminated"))?
+        };
+        let headers = std::str::from_utf8(&self.body[pos..headers_end])
+            .map_err(|_| malformed("the headers of a part are not valid UTF-8"))?
+            .split("\r\n")
+            .filter(|line| !line.is_empty())
+            .map(|line| match line.find(':') {
+                Some(i) => Ok((line[..i].trim(), line[i + 1..].trim())),
+                None => Err(malformed("a header of a part has no value")),
+            })
+            .collect::<Result<HeaderMap, _>>()?;
+
+        let data_start = if headers_end == pos {
+            pos + 2
+        } else {
+            headers_end + 4
+        };
+        let data_end = find(self.body, &self.delimiter, data_start)
+            .ok_or_else(|| malformed("the body ends before its closing boundary"))?;
+        if data_end - data_start > self.part_limit {
+            return Err(BodyError::TooLarge {
+                limit: self.part_limit,
+            });
+        }
+        self.pos = Some(data_end + self.delimiter.len());
+        Ok(Some(Part::new(headers, &self.body[data_start..data_end])))
+    }
+}
+
+impl<'a> Iterator for Multipart<'a> {
+    type Item = Result<Part<'a>, BodyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.next_part().transpose();
+        if !matches!(next, Some(Ok(_))) {
+            self.done = true;
+        }
+        next
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|i| i + from)
+}
+
+fn malformed(message: &str) -> BodyError {
+    BodyError::Invalid(format!("malformed multipart body: {}", message))
+}
+
+/// A part of a `multipart/form-data` body, usually a form field or an uploaded file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Part<'a> {
+    pub headers: HeaderMap,
+    pub data: &'a [u8],
+    name: Option<String>,
+    filename: Option<String>,
+}
+
+impl<'a> Part<'a> {
+    fn new(headers: HeaderMap, data: &'a [u8]) -> Self {
+        let disposition = headers.get("Content-Disposition").unwrap_or_default();
+        Part {
+            name: parameter(disposition, "name"),
+            filename: parameter(disposition, "filename"),
+            headers,
+            data,
+        }
+    }
+
+    /// The name of the form field, from the `Content-Disposition` header
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The name of the uploaded file, from the `Content-Disposition` header
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// The media type of the data. Parts without a `Content-Type` header are `text/plain`
+    pub fn content_type(&self) -> &str {
+        self.headers.get("Content-Type").unwrap_or("text/plain")
+    }
+
+    /// The data as text, which fails if it is not valid UTF-8
+    pub fn text(&self) -> Result<&'a str, BodyError> {
+        std::str::from_utf8(self.data).map_err(|e| BodyError::Invalid(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Signup {
+        name: String,
+        age: u32,
+        #[serde(default)]
+        topic: Vec<String>,
+    }
+
+    #[test]
+    fn parses_json_and_forms() {
+        let signup: Signup = json(
+            Some("Application/JSON; charset=utf-8"),
+            br#"{"name":"Ada","age":36}"#,
+            DEFAULT_BODY_LIMIT,
+        )
+        .unwrap();
+        assert_eq!(signup.name, "Ada");
+        assert!(json::<Signup>(
+            Some("application/vnd.api+json"),
+            br#"{"name":"Ada","age":36}"#,
+            100
+        )
+        .is_ok());
+
+        let signup: Signup = form(
+            Some("application/x-www-form-urlencoded"),
+            b"name=Ada+Lovelace&age=36&topic=math&topic=engines",
+            DEFAULT_BODY_LIMIT,
+        )
+        .unwrap();
+        assert_eq!(
+            signup,
+            Signup {
+                name: "Ada Lovelace".to_string(),
+                age: 36,
+                topic: vec!["math".to_string(), "engines".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn reports_bad_bodies() {
+        let err = json::<Signup>(Some("text/plain"), b"{}", DEFAULT_BODY_LIMIT).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected a body of type `application/json`, not `text/plain`"
+        );
+        assert_eq!(Response::from(err).status_code, 415);
+
+        let err = form::<Signup>(None, b"", DEFAULT_BODY_LIMIT).unwrap_err();
+        assert_eq!(Response::from(err).status_code, 415);
+
+        let err = json::<Signup>(Some(JSON), br#"{"name":"Ada","age":36}"#, 10).unwrap_err();
+        assert_eq!(err, BodyError::TooLarge { limit: 10 });
+        assert_eq!(Response::from(err).status_code, 413);
+
+        let err = form::<Signup>(Some(FORM), b"name=Ada&age=old", DEFAULT_BODY_LIMIT).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid body: `old`: invalid digit found in string"
+        );
+        let res = Response::from(err);
+        assert_eq!(res.status_code, 400);
+        assert_eq!(
+            res.body,
+            b"invalid body: `old`: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn reads_multipart_parts() {
+        let body = b"preamble\r\n--XyZ\r\n\
+            Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+            Hello\r\n--XyZ  \r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"a \\\"b\\\".txt\"\r\n\
+            Content-Type: application/octet-stream\r\n\r\n\
+            \x00\x01\r\n--X\r\n--XyZ--\r\n";
+        let parts = multipart(
+            Some("multipart/form-data; boundary=\"XyZ\""),
+            body,
+            DEFAULT_MULTIPART_LIMIT,
+        )
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].name(), Some("title"));
+        assert_eq!(parts[0].filename(), None);
+        assert_eq!(parts[0].content_type(), "text/plain");
+        assert_eq!(parts[0].text().unwrap(), "Hello");
+        assert_eq!(parts[1].name(), Some("file"));
+        assert_eq!(parts[1].filename(), Some("a \"b\".txt"));
+        assert_eq!(parts[1].content_type(), "application/octet-stream");
+        assert_eq!(parts[1].data, b"\x00\x01\r\n--X");
+    }
+
+    #[test]
+    fn stops_at_multipart_errors() {
+        let content_type = Some("multipart/form-data; boundary=b");
+        assert_eq!(
+            multipart(Some("multipart/form-data"), b"", DEFAULT_MULTIPART_LIMIT).unwrap_err(),
+            BodyError::Invalid("missing multipart boundary".to_string())
+        );
+
+        let body = b"--b\r\nContent-Disposition: form-data; name=a\r\n\r\n12345\r\n--b--";
+        let mut parts = multipart(content_type, body, DEFAULT_MULTIPART_LIMIT)
+            .unwrap()
+            .part_limit(4);
+        assert_eq!(parts.next(), Some(Err(BodyError::TooLarge { limit: 4 })));
+        assert_eq!(parts.next(), None);
+        assert_eq!(
+            multipart(content_type, body, body.len() - 1).unwrap_err(),
+            BodyError::TooLarge {
+                limit: body.len() - 1
+            }
+        );
+        assert!(multipart(content_type, body, body.len()).is_ok());
+
+        let body = b"--b\r\nContent-Disposition: form-data; name=a\r\n\r\n12345";
+        let mut parts = multipart(content_type, body, DEFAULT_MULTIPART_LIMIT).unwrap();
+        assert_eq!(
+            parts.next().unwrap().unwrap_err().to_string(),
+            "invalid body: malformed multipart body: the body ends before its closing boundary"
+        );
+        assert_eq!(parts.next(), None);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::body::{self, BodyError, Multipart, DEFAULT_BODY_LIMIT, DEFAULT_MULTIPART_LIMIT};
+use crate::{HeaderMap, Request, RequestRef};
+use serde::de::DeserializeOwned;
+use std::borrow::Cow;
+use wasmcloud_actor_core::trace::Carrier;
+
+impl<'a> RequestRef<'a> {
+    /// Copies the request into an owned [Request](struct.Request.html)
+    pub fn into_owned(self) -> Request {
+        Request {
+            method: self.method.into_owned(),
+            path: self.path.into_owned(),
+            query_string: self.query_string.into_owned(),
+            header: HeaderMap::from_fields(
+                self.header
+                    .into_iter()
+                    .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                    .collect(),
+                self.header_values.map(|lists| {
+                    lists
+                        .into_iter()
+                        .map(|(k, values)| {
+                            let values = values.into_iter().map(Cow::into_owned).collect();
+                            (k.into_owned(), values)
+                        })
+                        .collect()
+                }),
+            ),
+            body: self.body.to_vec(),
+        }
+    }
+
+    /// Parses a JSON body. See [Request::json](struct.Request.html#method.json)
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, BodyError> {
+        self.json_with_limit(DEFAULT_BODY_LIMIT)
+    }
+
+    /// Parses a JSON body of up to `limit` bytes
+    pub fn json_with_limit<T: DeserializeOwned>(&self, limit: usize) -> Result<T, BodyError> {
+        body::json(self.content_type(), self.body, limit)
+    }
+
+    /// Parses a form body. See [Request::form](struct.Request.html#method.form)
+    pub fn form<T: DeserializeOwned>(&self) -> Result<T, BodyError> {
+        self.form_with_limit(DEFAULT_BODY_LIMIT)
+    }
+
+    /// Parses a form body of up to `limit` bytes
+    pub fn form_with_limit<T: DeserializeOwned>(&self, limit: usize) -> Result<T, BodyError> {
+        body::form(self.content_type(), self.body, limit)
+    }
+
+    /// Reads a `multipart/form-data` body one part at a time. The parts borrow their data from
+    /// the payload delivered by the host. See
+    /// [Request::multipart](struct.Request.html#method.multipart)
+    pub fn multipart(&self) -> Result<Multipart<'a>, BodyError> {
+        self.multipart_with_limit(DEFAULT_MULTIPART_LIMIT)
+    }
+
+    /// Reads a multipart body of up to `limit` bytes
+    pub fn multipart_with_limit(&self, limit: usize) -> Result<Multipart<'a>, BodyError> {
+        body::multipart(self.content_type(), self.body, limit)
+    }
+
+    fn content_type(&self) -> Option<&str> {
+        let values = self.header_values.as_ref().and_then(|lists| {
+            lists
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("Content-Type"))
+                .and_then(|(_, values)| values.first())
+        });
+        let value = values.or_else(|| {
+            self.header
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("Content-Type"))
+                .map(|(_, value)| value)
+        });
+        value.map(|value| value.as_ref())
+    }
+}
+
+impl<'a> From<&'a Request> for RequestRef<'a> {
+    fn from(req: &'a Request) -> Self {
+        RequestRef {
+            method: Cow::Borrowed(&req.method),
+            path: Cow::Borrowed(&req.path),
+            query_string: Cow::Borrowed(&req.query_string),
+            header: req
+                .header
+                .to_joined()
+                .into_iter()
+                .map(|(k, v)| (Cow::Owned(k), Cow::Owned(v)))
+                .collect(),
+            header_values: Some(
+                req.header
+                    .to_lists()
+                    .into_iter()
+                    .map(|(k, values)| {
+                        (Cow::Owned(k), values.into_iter().map(Cow::Owned).collect())
+                    })
+                    .collect(),
+            ),
+            body: &req.body,
+        }
+    }
+}
+
+impl<'a> Carrier for RequestRef<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        Carrier::get(&self.header, key)
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        Carrier::set(&mut self.header, key, value)
+    }
+}
@@ -0,0 +1,357 @@
+//! Parsing of the `Cookie` header and building of `Set-Cookie` headers
+
+use crate::route::percent_decode;
+use crate::{HeaderMap, Response, ResponseBuilder};
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Splits the `Cookie` headers of a request into percent-decoded name/value pairs, in order.
+/// Pairs without a name or `=` are skipped
+pub(crate) fn parse(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .get_all("Cookie")
+        .into_iter()
+        .flat_map(|header| header.split(';'))
+        .filter_map(|pair| {
+            let i = pair.find('=')?;
+            let name = pair[..i].trim();
+            if name.is_empty() {
+                return None;
+            }
+            let value = pair[i + 1..].trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            Some((percent_decode(name), percent_decode(value)))
+        })
+        .collect()
+}
+
+/// The `SameSite` attribute of a cookie, which controls whether it is sent with cross-site
+/// requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    /// Browsers only accept `SameSite=None` on cookies that are also `Secure`
+    None,
+}
+
+impl fmt::Display for SameSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        })
+    }
+}
+
+/// A cookie to set on the client, with the attributes of its `Set-Cookie` header.
+///
+/// Characters that can't appear in a cookie name or value, such as spaces, `;` and `%`, are
+/// percent-encoded, and [Request::cookies](struct.Request.html#method.cookies) decodes them, so
+/// any string can be stored in a cookie.
+///
+/// ```
+/// use std::time::Duration;
+/// use wasmcloud_actor_http_server::{Cookie, Response, SameSite};
+///
+/// let cookie = Cookie::new("theme", "dark mode")
+///     .path("/")
+///     .max_age(Duration::from_secs(3600))
+///     .secure(true)
+///     .http_only(true)
+///     .same_site(SameSite::Lax);
+/// let res = Response::builder().cookie(&cookie).build();
+/// assert_eq!(
+///     res.header["Set-Cookie"],
+///     "theme=dark%20mode; Path=/; Max-Age=3600; Secure; HttpOnly; SameSite=Lax"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cookie {
+    name: String,
+    value: String,
+    path: Option<String>,
+    domain: Option<String>,
+    max_age: Option<Duration>,
+    expires: Option<SystemTime>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+}
+
+impl Cookie {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Cookie {
+        Cookie {
+            name: name.into(),
+            value: value.into(),
+            path: None,
+            domain: None,
+            max_age: None,
+            expires: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        }
+    }
+
+    /// A cookie that removes the cookie with the given name from the client. Its path and domain
+    /// must match those the cookie was set with
+    pub fn removal(name: impl Into<String>) -> Cookie {
+        Cookie::new(name, "")
+            .max_age(Duration::from_secs(0))
+            .expires(UNIX_EPOCH)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Replaces the value, keeping the attributes
+    pub fn with_value(mut self, value: impl Into<String>) -> Cookie {
+        self.value = value.into();
+        self
+    }
+
+    /// Sets the `Path` attribute. A `;` or control character in the path, such as CR or LF,
+    /// which would end the attribute or the header, is percent-encoded
+    pub fn path(mut self, path: impl Into<String>) -> Cookie {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Sets the `Domain` attribute. A `;` or control character in the domain is percent-encoded,
+    /// so a domain taken from the request, such as its `Host` header, can't add attributes or
+    /// headers, and an invalid one is ignored by the client
+    pub fn domain(mut self, domain: impl Into<String>) -> Cookie {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// How long the cookie is kept, in whole seconds. Takes precedence over `expires`
+    pub fn max_age(mut self, max_age: Duration) -> Cookie {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub fn expires(mut self, expires: SystemTime) -> Cookie {
+        self.expires = Some(expires);
+        self
+    }
+
+    /// Only sends the cookie over HTTPS
+    pub fn secure(mut self, secure: bool) -> Cookie {
+        self.secure = secure;
+        self
+    }
+
+    /// Hides the cookie from scripts running in the browser
+    pub fn http_only(mut self, http_only: bool) -> Cookie {
+        self.http_only = http_only;
+        self
+    }
+
+    pub fn same_site(mut self, same_site: SameSite) -> Cookie {
+        self.same_site = Some(same_site);
+        self
+    }
+}
+
+/// Formats the value of the cookie's `Set-Cookie` header
+impl fmt::Display for Cookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}={}",
+            encode(&self.name, is_token_char),
+            encode(&self.value, is_cookie_octet)
+        )?;
+        if let Some(path) = &self.path {
+            write!(f, "; Path={}", encode_attribute(path))?;
+        }
+        if let Some(domain) = &self.domain {
+            write!(f, "; Domain={}", encode_attribute(domain))?;
+        }
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={}", max_age.as_secs())?;
+        }
+        if let Some(expires) = self.expires {
+            write!(f, "; Expires={}", http_date(expires))?;
+        }
+        if self.secure {
+            f.write_str("; Secure")?;
+        }
+        if self.http_only {
+            f.write_str("; HttpOnly")?;
+        }
+        if let Some(same_site) = self.same_site {
+            write!(f, "; SameSite={}", same_site)?;
+        }
+        Ok(())
+    }
+}
+
+impl Response {
+    /// Adds a `Set-Cookie` header for the cookie, keeping the cookies already set
+    pub fn add_cookie(&mut self, cookie: &Cookie) {
+        self.header.append("Set-Cookie", cookie.to_string());
+    }
+}
+
+impl ResponseBuilder {
+    /// Adds a `Set-Cookie` header for the cookie, keeping the cookies already set
+    pub fn cookie(self, cookie: &Cookie) -> Self {
+        self.header("Set-Cookie", cookie.to_string())
+    }
+}
+
+/// Percent-encodes the `;` and control characters of an attribute value, which RFC 6265 allows
+/// in `Path` and `Domain` values apart from those. Other characters, including `%`, are kept, so
+/// that paths that are already percent-encoded are written as they are
+fn encode_attribute(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == ';' || c.is_ascii_control() {
+            out.push_str(&format!("%{:02X}", c as u8));
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Percent-encodes the bytes that are not allowed, along with `%`
+fn encode(s: &str, allowed: fn(u8) -> bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if allowed(b) && b != b'%' {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+fn is_token_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+/// The characters allowed in a cookie value by RFC 6265
+fn is_cookie_octet(b: u8) -> bool {
+    matches!(b, 0x21 | 0x23..=0x2B | 0x2D..=0x3A | 0x3C..=0x5B | 0x5D..=0x7E)
+}
+
+/// Formats a time as an IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`
+fn http_date(time: SystemTime) -> String {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // Converts the days since the epoch to a date of the proleptic Gregorian calendar, with
+    // years starting in March so that leap days come last
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[(days % 7) as usize],
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_cookie_headers() {
+        let mut headers = HeaderMap::new();
+        headers.append("Cookie", "a=1; b=\"two\";c=x%3By; junk; =x");
+        headers.append("cookie", "a=3; empty=");
+        assert_eq!(
+            parse(&headers),
+            vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "two".to_string()),
+                ("c".to_string(), "x;y".to_string()),
+                ("a".to_string(), "3".to_string()),
+                ("empty".to_string(), "".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn formats_set_cookie_headers() {
+        let cookie = Cookie::new("session id", "a;b\"c%")
+            .domain("example.com")
+            .path("/app")
+            .expires(UNIX_EPOCH + Duration::from_secs(784_111_777))
+            .same_site(SameSite::Strict);
+        assert_eq!(
+            cookie.to_string(),
+            "session%20id=a%3Bb%22c%25; Path=/app; Domain=example.com; \
+             Expires=Sun, 06 Nov 1994 08:49:37 GMT; SameSite=Strict"
+        );
+
+        let mut res = Response::ok();
+        res.add_cookie(&cookie);
+        res.add_cookie(&Cookie::removal("old").path("/"));
+        assert_eq!(
+            res.header.get_all("Set-Cookie")[1],
+            "old=; Path=/; Max-Age=0; Expires=Thu, 01 Jan 1970 00:00:00 GMT"
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Cookie", cookie.to_string());
+        assert_eq!(
+            parse(&headers)[0],
+            ("session id".to_string(), "a;b\"c%".to_string())
+        );
+    }
+
+    #[test]
+    fn encodes_attribute_injection() {
+        let cookie = Cookie::new("a", "b")
+            .path("/a%20b;\r\nSet-Cookie: admin=1")
+            .domain("example.com; Secure");
+        assert_eq!(
+            cookie.to_string(),
+            "a=b; Path=/a%20b%3B%0D%0ASet-Cookie: admin=1; Domain=example.com%3B Secure"
+        );
+    }
+
+    #[test]
+    fn formats_dates() {
+        let at = |secs| http_date(UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(at(951_782_400), "Tue, 29 Feb 2000 00:00:00 GMT");
+        assert_eq!(at(4_107_542_399), "Sun, 28 Feb 2100 23:59:59 GMT");
+        assert_eq!(at(1_704_067_199), "Sun, 31 Dec 2023 23:59:59 GMT");
+    }
+}
@@ -0,0 +1,255 @@
+//! Signing and encryption of cookies, so that actors can keep small amounts of state on the client
+
+use crate::{Cookie, Request};
+use aes_gcm_siv::aead::{Aead, KeyInit, Payload};
+use aes_gcm_siv::{Aes256GcmSiv, Nonce};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt;
+use wasmcloud_actor_core::FromLinkValue;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The length, in bytes, of the shortest secret a [CookieKey](struct.CookieKey.html) accepts
+pub const MIN_SECRET_LEN: usize = 32;
+
+const NONCE_LEN: usize = 12;
+
+/// The keys that sign and encrypt cookies, derived from a single secret.
+///
+/// Signed cookies can be read by the client but not changed, and encrypted cookies can be
+/// neither read nor changed. Both are bound to the cookie name, so a value can't be moved to
+/// another cookie. Encryption uses AES-256-GCM-SIV with a nonce derived from the cookie, so that
+/// no source of randomness is needed: encrypting the same value twice gives the same cookie.
+///
+/// The secret is usually part of the link configuration, as a base64-encoded string, and parsed
+/// with the rest of it:
+///
+/// ```
+/// use std::collections::HashMap;
+/// use wasmcloud_actor_core::FromLinkValues;
+/// use wasmcloud_actor_http_server::{Cookie, CookieKey, Request};
+///
+/// #[derive(FromLinkValues)]
+/// struct Config {
+///     #[link_values(rename = "COOKIE_SECRET")]
+///     cookie_key: CookieKey,
+/// }
+///
+/// let mut values = HashMap::new();
+/// values.insert(
+///     "COOKIE_SECRET".to_string(),
+///     "c2VjcmV0IG9mIGF0IGxlYXN0IHRoaXJ0eS10d28gYnl0ZXM=".to_string(),
+/// );
+/// let config = Config::from_link_values(&values).unwrap();
+///
+/// let cookie = config.cookie_key.encrypt(Cookie::new("session", "user=7").http_only(true));
+/// let mut req = Request::default();
+/// req.header.insert("Cookie", format!("session={}", cookie.value()));
+/// let session = req.encrypted_cookie(&config.cookie_key, "session");
+/// assert_eq!(session.as_deref(), Some("user=7"));
+/// ```
+#[derive(Clone)]
+pub struct CookieKey {
+    signing: [u8; 32],
+    encryption: [u8; 32],
+    nonce: [u8; 32],
+}
+
+impl CookieKey {
+    /// Derives the keys from a secret, or returns `None` if the secret is shorter than
+    /// [MIN_SECRET_LEN](constant.MIN_SECRET_LEN.html) bytes
+    pub fn from_secret(secret: &[u8]) -> Option<CookieKey> {
+        if secret.len() < MIN_SECRET_LEN {
+            return None;
+        }
+        let derive = |label: &[u8]| -> [u8; 32] {
+            let mut mac = <HmacSha256 as Mac>::new_from_slice(secret).unwrap();
+            mac.update(label);
+            mac.finalize().into_bytes().into()
+        };
+        Some(CookieKey {
+            signing: derive(b"wasmcloud cookie signing"),
+            encryption: derive(b"wasmcloud cookie encryption"),
+            nonce: derive(b"wasmcloud cookie nonce"),
+        })
+    }
+
+    /// Prefixes the value of the cookie with a signature
+    pub fn sign(&self, cookie: Cookie) -> Cookie {
+        let tag = self.mac(&self.signing, cookie.name(), cookie.value());
+        let value = format!("{}.{}", encode(&tag), cookie.value());
+        cookie.with_value(value)
+    }
+
+    /// The value of a signed cookie, or `None` if its signature is invalid
+    pub fn verify(&self, name: &str, value: &str) -> Option<String> {
+        let i = value.find('.')?;
+        let (tag, value) = (decode(&value[..i])?, &value[i + 1..]);
+        let mut mac = <HmacSha256 as Mac>::new_from_slice(&self.signing).unwrap();
+        update(&mut mac, name, value);
+        mac.verify_slice(&tag).ok()?;
+        Some(value.to_string())
+    }
+
+    /// Replaces the value of the cookie with its encryption
+    pub fn encrypt(&self, cookie: Cookie) -> Cookie {
+        let nonce = self.mac(&self.nonce, cookie.name(), cookie.value());
+        let nonce = Nonce::from_slice(&nonce[..NONCE_LEN]);
+        let payload = Payload {
+            msg: cookie.value().as_bytes(),
+            aad: cookie.name().as_bytes(),
+        };
+        let mut value = nonce.to_vec();
+        value.extend(self.cipher().encrypt(nonce, payload).unwrap());
+        let value = encode(&value);
+        cookie.with_value(value)
+    }
+
+    /// The value of an encrypted cookie, or `None` if it can't be decrypted
+    pub fn decrypt(&self, name: &str, value: &str) -> Option<String> {
+        let value = decode(value)?;
+        if value.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, msg) = value.split_at(NONCE_LEN);
+        let payload = Payload {
+            msg,
+            aad: name.as_bytes(),
+        };
+        let value = self
+            .cipher()
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .ok()?;
+        String::from_utf8(value).ok()
+    }
+
+    fn mac(&self, key: &[u8], name: &str, value: &str) -> Vec<u8> {
+        let mut mac = <HmacSha256 as Mac>::new_from_slice(key).unwrap();
+        update(&mut mac, name, value);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    fn cipher(&self) -> Aes256GcmSiv {
+        Aes256GcmSiv::new_from_slice(&self.encryption).unwrap()
+    }
+}
+
+/// Keeps the keys out of logs
+impl fmt::Debug for CookieKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CookieKey { .. }")
+    }
+}
+
+/// Parses a base64-encoded secret of at least [MIN_SECRET_LEN](constant.MIN_SECRET_LEN.html)
+/// bytes
+impl FromLinkValue for CookieKey {
+    fn from_link_value(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        base64::decode(value)
+            .or_else(|_| base64::decode_config(value, base64::URL_SAFE_NO_PAD))
+            .ok()
+            .and_then(|secret| CookieKey::from_secret(&secret))
+            .ok_or_else(|| {
+                format!(
+                    "a base64-encoded secret of at least {} bytes",
+                    MIN_SECRET_LEN
+                )
+            })
+    }
+}
+
+impl Request {
+    /// The value of the first cookie with the given name and a valid signature. See
+    /// [CookieKey::sign](struct.CookieKey.html#method.sign)
+    pub fn signed_cookie(&self, key: &CookieKey, name: &str) -> Option<String> {
+        self.cookies()
+            .into_iter()
+            .filter(|(n, _)| n == name)
+            .find_map(|(_, value)| key.verify(name, &value))
+    }
+
+    /// The decrypted value of the first cookie with the given name that can be decrypted. See
+    /// [CookieKey::encrypt](struct.CookieKey.html#method.encrypt)
+    pub fn encrypted_cookie(&self, key: &CookieKey, name: &str) -> Option<String> {
+        self.cookies()
+            .into_iter()
+            .filter(|(n, _)| n == name)
+            .find_map(|(_, value)| key.decrypt(name, &value))
+    }
+}
+
+/// Authenticates the name along with the value, with its length first so that the boundary
+/// between them is unambiguous
+fn update(mac: &mut HmacSha256, name: &str, value: &str) {
+    mac.update(&(name.len() as u64).to_be_bytes());
+    mac.update(name.as_bytes());
+    mac.update(value.as_bytes());
+}
+
+fn encode(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn decode(s: &str) -> Option<Vec<u8>> {
+    base64::decode_config(s, base64::URL_SAFE_NO_PAD).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key(secret: &[u8]) -> CookieKey {
+        CookieKey::from_secret(secret).unwrap()
+    }
+
+    #[test]
+    fn signs_cookies() {
+        let key = key(&[7; 32]);
+        let cookie = key.sign(Cookie::new("cart", "3 items"));
+        assert!(cookie.value().ends_with(".3 items"));
+        assert_eq!(
+            key.verify("cart", cookie.value()).as_deref(),
+            Some("3 items")
+        );
+
+        let tampered = cookie.value().replace("3 items", "9 items");
+        assert_eq!(key.verify("cart", &tampered), None);
+        assert_eq!(key.verify("other", cookie.value()), None);
+        assert_eq!(CookieKey::from_secret(&[7; 31]).map(|_| ()), None);
+        assert_eq!(self::key(&[8; 32]).verify("cart", cookie.value()), None);
+    }
+
+    #[test]
+    fn encrypts_cookies() {
+        let key = key(b"an example secret that is long enough");
+        let cookie = key.encrypt(Cookie::new("session", "user=7").path("/"));
+        assert!(!cookie.value().contains("user"));
+        assert!(cookie.to_string().ends_with("; Path=/"));
+        assert_eq!(
+            key.decrypt("session", cookie.value()).as_deref(),
+            Some("user=7")
+        );
+        assert_eq!(key.decrypt("other", cookie.value()), None);
+        assert_eq!(key.decrypt("session", "garbage"), None);
+
+        let mut req = Request::default();
+        req.header.append("Cookie", "session=stale");
+        req.header.append("Cookie", cookie.to_string());
+        assert_eq!(
+            req.encrypted_cookie(&key, "session").as_deref(),
+            Some("user=7")
+        );
+        assert_eq!(req.signed_cookie(&key, "session"), None);
+    }
+
+    #[test]
+    fn parses_link_values() {
+        assert!(CookieKey::from_link_value(&base64::encode([1u8; 32])).is_ok());
+        assert_eq!(
+            CookieKey::from_link_value("c2hvcnQ=").unwrap_err(),
+            "a base64-encoded secret of at least 32 bytes"
+        );
+    }
+}
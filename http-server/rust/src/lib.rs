@@ -21,7 +21,7 @@
 //! }
 //!
 //! fn req_handler(req: http::Request) -> HandlerResult<http::Response> {
-//!     let method = req.method();
+//!     let method = req.method()?;
 //!     let segments = req.path_segments();
 //!
 //!     match (method, &*segments)  {
@@ -38,19 +38,147 @@
 //!     Ok(http::Response::ok())
 //! }
 //! ```
+//!
+//! For more than a handful of routes, a [Router](struct.Router.html) matches requests against path patterns such
+//! as `/v1/users/:id`, answers requests for unknown paths and methods with `404` and `405` responses, and can be
+//! registered directly as the request handler:
+//!
+//! ```
+//! use wasmcloud_actor_http_server as http;
+//! use wasmcloud_actor_core as actor;
+//! use http::{Handlers, Response, Router};
+//!
+//! #[actor::init]
+//! fn init() {
+//!     let router = Router::new()
+//!         .get("/v1/users/:id", |_req, params| Ok(Response::json(params.get("id"), 200, "OK")))
+//!         .put("/v1/users/:id", |_req, _params| Ok(Response::ok()));
+//!     Handlers::register_handle_request(move |req| router.handle(req));
+//! }
+//! ```
+//!
+//! Request and response headers are held in a [HeaderMap](struct.HeaderMap.html), which looks names up
+//! case-insensitively and keeps every value of repeated headers such as `Set-Cookie`. It is shared with the HTTP
+//! client interface, so headers can be passed between the two as they are.
+//!
+//! Request bodies are parsed with [json](struct.Request.html#method.json), [form](struct.Request.html#method.form)
+//! and [multipart](struct.Request.html#method.multipart), which check the `Content-Type` of the request and limit
+//! the size of the body. Their [BodyError](enum.BodyError.html) converts into a ready-made `415`, `413` or `400`
+//! response.
+//!
+//! Responses can be built fluently from a [StatusCode](enum.StatusCode.html), which carries the canonical reason
+//! phrase of each registered code, with [Response::builder](struct.Response.html#method.builder). Shortcuts such as
+//! [see_other](struct.Response.html#method.see_other), [created](struct.Response.html#method.created) and
+//! [too_many_requests](struct.Response.html#method.too_many_requests) create common responses along with the
+//! headers they need.
+//!
+//! ```
+//! use wasmcloud_actor_http_server::{Response, StatusCode};
+//!
+//! let res = Response::builder()
+//!     .status(StatusCode::Accepted)
+//!     .header("Cache-Control", "no-store")
+//!     .text("queued");
+//! assert_eq!(res.status, "Accepted");
+//! ```
+//!
+//! Cookies sent by the client are read with [cookies](struct.Request.html#method.cookies), and set with a
+//! [Cookie](struct.Cookie.html) added to the response. With the `secure-cookies` feature, a
+//! [CookieKey](struct.CookieKey.html) signs or encrypts cookies, so that small amounts of state, such as a session,
+//! can be kept on the client.
+//!
+//! Every request is handled in the trace identified by its `traceparent` header, if it has one, so calls made while
+//! handling it belong to the same trace. See `wasmcloud_actor_core::trace`.
+//!
+//! Handlers can be plain functions, as above, or closures that capture configuration or other state.
+//!
+//! Handlers that process large request bodies can register with `register_handle_request_ref` instead,
+//! and receive a [RequestRef](struct.RequestRef.html) whose body borrows from the payload delivered by the host
+//! rather than being copied out of it.
 
-pub mod generated;
+mod body;
+mod borrowed;
+mod cookie;
+#[cfg(feature = "secure-cookies")]
+mod cookie_key;
+pub mod generated {
+    include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+}
+mod query;
+mod response;
 mod route;
+#[cfg(feature = "guest")]
+mod router;
+mod status;
+use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::collections::HashMap;
 
-pub use route::Method;
+pub use body::{
+    BodyError, Multipart, Part, DEFAULT_BODY_LIMIT, DEFAULT_MULTIPART_LIMIT, DEFAULT_PART_LIMIT,
+};
+pub use cookie::{Cookie, SameSite};
+#[cfg(feature = "secure-cookies")]
+pub use cookie_key::{CookieKey, MIN_SECRET_LEN};
+pub use query::QueryError;
+pub use response::ResponseBuilder;
+pub use route::{Method, MethodError};
+#[cfg(feature = "guest")]
+pub use router::{ParamError, Params, Router};
+pub use status::StatusCode;
 
 #[cfg(feature = "guest")]
 pub use generated::Handlers;
-pub use generated::{deserialize, serialize, Request, Response};
+pub use generated::{deserialize, serialize, RequestRef, CONTRACT};
+pub use wasmcloud_actor_core::header::HeaderMap;
 
+use serde::Deserialize;
 use std::str::FromStr;
+use wasmcloud_actor_core::trace::Carrier;
+
+/// HTTP Request object
+#[derive(Debug, PartialEq, Deserialize, Serialize, Default, Clone)]
+pub struct Request {
+    #[serde(rename = "method")]
+    pub method: String,
+    #[serde(rename = "path")]
+    pub path: String,
+    #[serde(rename = "queryString")]
+    pub query_string: String,
+    /// The headers of the request, carried in the `header` and `headerValues` fields of the
+    /// contract
+    #[serde(flatten, with = "wasmcloud_actor_core::header::fields")]
+    pub header: HeaderMap,
+    #[serde(with = "serde_bytes")]
+    #[serde(rename = "body")]
+    pub body: Vec<u8>,
+}
+
+/// HTTP Response object
+#[derive(Debug, PartialEq, Deserialize, Serialize, Default, Clone)]
+pub struct Response {
+    #[serde(rename = "statusCode")]
+    pub status_code: u32,
+    #[serde(rename = "status")]
+    pub status: String,
+    /// The headers of the response, carried in the `header` and `headerValues` fields of the
+    /// contract
+    #[serde(flatten, with = "wasmcloud_actor_core::header::fields")]
+    pub header: HeaderMap,
+    #[serde(with = "serde_bytes")]
+    #[serde(rename = "body")]
+    pub body: Vec<u8>,
+}
+
+/// Carries the trace context of a request in its headers
+impl Carrier for Request {
+    fn get(&self, key: &str) -> Option<&str> {
+        Carrier::get(&self.header, key)
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        Carrier::set(&mut self.header, key, value)
+    }
+}
 
 impl Request {
     pub fn path_segments(&self) -> Vec<&str> {
@@ -61,20 +189,149 @@ impl Request {
             .collect::<Vec<_>>()
     }
 
-    pub fn method(&self) -> Method {
-        Method::from_str(&self.method).unwrap()
+    /// The percent-decoded key/value pairs of the query string, in order
+    pub fn query_pairs(&self) -> Vec<(String, String)> {
+        query::pairs(&self.query_string)
+    }
+
+    /// Parses the query string into a struct or map. Keys that appear several times can be
+    /// collected into a `Vec`, and `Option` fields may be missing or empty. A `QueryError` converts
+    /// into a `400 Bad Request` response, which a [Router](struct.Router.html) sends on its own
+    /// when a handler returns it
+    ///
+    /// ```
+    /// # use wasmcloud_actor_http_server::Request;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Search {
+    ///     q: String,
+    ///     page: Option<u32>,
+    ///     #[serde(default)]
+    ///     tag: Vec<String>,
+    /// }
+    ///
+    /// let req = Request {
+    ///     query_string: "q=hello+world&tag=a&tag=b".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let search: Search = req.query().unwrap();
+    /// assert_eq!(search.q, "hello world");
+    /// assert_eq!(search.page, None);
+    /// assert_eq!(search.tag, ["a", "b"]);
+    /// ```
+    pub fn query<T: DeserializeOwned>(&self) -> Result<T, QueryError> {
+        query::from_pairs(self.query_pairs())
+    }
+
+    /// Parses a JSON body of up to [DEFAULT_BODY_LIMIT](constant.DEFAULT_BODY_LIMIT.html) bytes.
+    /// The `Content-Type` must be `application/json` or another JSON media type such as
+    /// `application/problem+json`. A `BodyError` converts into a `415`, `413` or `400` response,
+    /// which a [Router](struct.Router.html) sends on its own when a handler returns it
+    ///
+    /// ```
+    /// # use wasmcloud_actor_http_server::{Request, Response};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct NewUser {
+    ///     name: String,
+    /// }
+    ///
+    /// let mut req = Request {
+    ///     body: br#"{"name":"ada"}"#.to_vec(),
+    ///     ..Default::default()
+    /// };
+    /// let res = Response::from(req.json::<NewUser>().unwrap_err());
+    /// assert_eq!(res.status_code, 415);
+    ///
+    /// req.header.insert("Content-Type", "application/json");
+    /// assert_eq!(req.json::<NewUser>().unwrap().name, "ada");
+    /// ```
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, BodyError> {
+        self.json_with_limit(DEFAULT_BODY_LIMIT)
+    }
+
+    /// Parses a JSON body of up to `limit` bytes. See [json](#method.json)
+    pub fn json_with_limit<T: DeserializeOwned>(&self, limit: usize) -> Result<T, BodyError> {
+        body::json(self.header.get("Content-Type"), &self.body, limit)
+    }
+
+    /// Parses an `application/x-www-form-urlencoded` body of up to
+    /// [DEFAULT_BODY_LIMIT](constant.DEFAULT_BODY_LIMIT.html) bytes, as sent by HTML forms, in the
+    /// same way as [query](#method.query) parses query strings
+    pub fn form<T: DeserializeOwned>(&self) -> Result<T, BodyError> {
+        self.form_with_limit(DEFAULT_BODY_LIMIT)
+    }
+
+    /// Parses a form body of up to `limit` bytes. See [form](#method.form)
+    pub fn form_with_limit<T: DeserializeOwned>(&self, limit: usize) -> Result<T, BodyError> {
+        body::form(self.header.get("Content-Type"), &self.body, limit)
+    }
+
+    /// Reads a `multipart/form-data` body of up to
+    /// [DEFAULT_MULTIPART_LIMIT](constant.DEFAULT_MULTIPART_LIMIT.html) bytes, such as a form with
+    /// file uploads, one part at a time. Handlers that receive large uploads can use
+    /// [RequestRef::multipart](struct.RequestRef.html#method.multipart) to avoid copying the body
+    /// altogether
+    ///
+    /// ```
+    /// # use wasmcloud_actor_http_server::{BodyError, Request};
+    /// # fn main() -> Result<(), BodyError> {
+    /// # let mut req = Request::default();
+    /// # req.header.insert("Content-Type", "multipart/form-data; boundary=b");
+    /// # req.body = b"--b\r\nContent-Disposition: form-data; name=\"f\"; filename=\"a.txt\"\r\n\r\nhi\r\n--b--".to_vec();
+    /// for part in req.multipart()?.part_limit(64 * 1024) {
+    ///     let part = part?;
+    ///     if let Some(filename) = part.filename() {
+    ///         println!("{} ({} bytes)", filename, part.data.len());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn multipart(&self) -> Result<Multipart<'_>, BodyError> {
+        self.multipart_with_limit(DEFAULT_MULTIPART_LIMIT)
+    }
+
+    /// Reads a multipart body of up to `limit` bytes. See [multipart](#method.multipart)
+    pub fn multipart_with_limit(&self, limit: usize) -> Result<Multipart<'_>, BodyError> {
+        body::multipart(self.header.get("Content-Type"), &self.body, limit)
+    }
+
+    /// The percent-decoded name/value pairs of the `Cookie` headers, in order
+    pub fn cookies(&self) -> Vec<(String, String)> {
+        cookie::parse(&self.header)
+    }
+
+    /// The value of the first cookie with the given name
+    pub fn cookie(&self, name: &str) -> Option<String> {
+        self.cookies()
+            .into_iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+
+    /// The method of the request. Methods other than the standard ones are returned as a
+    /// `Method::Extension`, and a `MethodError` if the method is not a valid token
+    pub fn method(&self) -> Result<Method, MethodError> {
+        Method::from_str(&self.method)
     }
 }
 
 impl Response {
-    /// Creates a response with a given status code and serializes the given payload as JSON
+    /// Creates a response with a given status code and serializes the given payload as JSON, with
+    /// a `Content-Type` of `application/json`. [Response::builder](#method.builder) can do the
+    /// same with a [StatusCode](enum.StatusCode.html) instead of a code and reason phrase
     pub fn json<T>(payload: T, status_code: u32, status: &str) -> Response
     where
         T: Serialize,
     {
+        let mut header = HeaderMap::new();
+        header.insert("Content-Type", "application/json");
         Response {
             body: serde_json::to_string(&payload).unwrap().into_bytes(),
-            header: HashMap::new(),
+            header,
             status: status.to_string(),
             status_code,
         }
@@ -82,39 +339,25 @@ impl Response {
 
     /// Handy shortcut for creating a 404/Not Found response
     pub fn not_found() -> Response {
-        Response {
-            status: "Not Found".to_string(),
-            status_code: 404,
-            ..Default::default()
-        }
+        Response::new(StatusCode::NotFound)
     }
 
     /// Useful shortcut for creating a 200/OK response
     pub fn ok() -> Response {
-        Response {
-            status: "OK".to_string(),
-            status_code: 200,
-            ..Default::default()
-        }
+        Response::new(StatusCode::Ok)
     }
 
     /// Useful shortcut for creating a 500/Internal Server Error response
     pub fn internal_server_error(msg: &str) -> Response {
         Response {
-            status: "Internal Server Error".to_string(),
-            status_code: 500,
             body: msg.to_string().as_bytes().into(),
-            ..Default::default()
+            ..Response::new(StatusCode::InternalServerError)
         }
     }
 
     /// Shortcut for creating a 400/Bad Request response
     pub fn bad_request() -> Response {
-        Response {
-            status: "Bad Request".to_string(),
-            status_code: 400,
-            ..Default::default()
-        }
+        Response::new(StatusCode::BadRequest)
     }
 }
 
@@ -123,9 +366,19 @@ impl Response {
 mod test {
     extern crate wapc_guest;
     use crate::{Handlers, Request, Response};
+    use std::sync::{Mutex, MutexGuard};
     use wapc_guest::HandlerResult;
+
+    /// Handlers are registered for the whole process, so the tests that register one for
+    /// `HandleRequest` take turns
+    pub(crate) fn handle_request_lock() -> MutexGuard<'static, ()> {
+        static LOCK: Mutex<()> = Mutex::new(());
+        LOCK.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
     #[test]
     fn it_works() {
+        let _lock = handle_request_lock();
         Handlers::register_handle_request(hr);
         assert!(true);
     }
@@ -139,8 +392,7 @@ mod test {
 #[cfg(feature = "guest")]
 mod path_segments {
     extern crate wapc_guest;
-    use crate::Request;
-    use std::collections::HashMap;
+    use crate::{HeaderMap, Request};
 
     #[test]
     fn empty() {
@@ -188,9 +440,63 @@ mod path_segments {
         Request {
             method: "GET".to_string(),
             body: Vec::new(),
-            header: HashMap::new(),
+            header: HeaderMap::new(),
             path: path.to_string(),
             query_string: "".to_string(),
         }
     }
 }
+
+#[cfg(test)]
+#[cfg(feature = "guest")]
+mod request_ref {
+    extern crate wapc_guest;
+    use crate::test::handle_request_lock;
+    use crate::{serialize, Handlers, HeaderMap, Request, RequestRef, Response};
+    use wapc_guest::HandlerResult;
+    use wasmcloud_actor_core::testing;
+
+    #[test]
+    fn borrows_body_from_payload() {
+        let _lock = handle_request_lock();
+        Handlers::register_handle_request_ref(echo);
+        let mut header = HeaderMap::new();
+        header.insert("Content-Type", "text/plain");
+        header.append("Accept", "text/plain");
+        header.append("Accept", "text/html");
+        let request = Request {
+            method: "POST".to_string(),
+            path: "/echo".to_string(),
+            query_string: "".to_string(),
+            header,
+            body: b"hello".to_vec(),
+        };
+
+        let res: Response = testing::invoke("HandleRequest", &request).unwrap();
+        assert_eq!(
+            res.body,
+            b"POST /echo text/plain text/plain, text/html hello"
+        );
+
+        let payload = serialize(&request).unwrap();
+        let borrowed: RequestRef =
+            wasmcloud_actor_core::codec::deserialize_borrowed(&payload).unwrap();
+        assert_eq!(borrowed.into_owned(), request);
+        assert_eq!(RequestRef::from(&request).into_owned(), request);
+    }
+
+    fn echo(req: RequestRef) -> HandlerResult<Response> {
+        let body = format!(
+            "{} {} {} {} {}",
+            req.method,
+            req.path,
+            req.header["Content-Type"],
+            req.header["Accept"],
+            std::str::from_utf8(req.body)?
+        );
+        Ok(Response {
+            body: body.into_bytes(),
+            ..Response::ok()
+        })
+    }
+}
@@ -0,0 +1,387 @@
+//! Parsing of `application/x-www-form-urlencoded` query strings into pairs and typed structs
+
+use crate::route::percent_decode;
+use crate::Response;
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use std::error::Error;
+use std::fmt;
+
+/// Splits a query string into its percent-decoded key/value pairs, in order. `+` is decoded as a
+/// space, and a key without `=` has an empty value
+pub(crate) fn pairs(query: &str) -> Vec<(String, String)> {
+    query
+        .trim_start_matches('?')
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = match pair.find('=') {
+                Some(i) => (&pair[..i], &pair[i + 1..]),
+                None => (pair, ""),
+            };
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+fn decode(s: &str) -> String {
+    percent_decode(&s.replace('+', " "))
+}
+
+/// Deserializes a struct or map from key/value pairs. Repeated keys are collected into sequences
+pub(crate) fn from_pairs<T: DeserializeOwned>(
+    pairs: Vec<(String, String)>,
+) -> Result<T, QueryError> {
+    let mut grouped: Vec<(String, Vec<String>)> = Vec::new();
+    for (key, value) in pairs {
+        match grouped.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, values)) => values.push(value),
+            None => grouped.push((key, vec![value])),
+        }
+    }
+    T::deserialize(PairsDeserializer {
+        pairs: grouped.into_iter(),
+        value: None,
+    })
+}
+
+/// A query string that doesn't match the type it is parsed into. Converts into a `400 Bad Request`
+/// [Response](struct.Response.html) describing the problem
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    pub(crate) message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid query string: {}", self.message)
+    }
+}
+
+impl Error for QueryError {}
+
+impl de::Error for QueryError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        QueryError {
+            message: msg.to_string(),
+        }
+    }
+}
+
+impl From<QueryError> for Response {
+    fn from(e: QueryError) -> Response {
+        Response {
+            body: e.to_string().into_bytes(),
+            ..Response::bad_request()
+        }
+    }
+}
+
+struct PairsDeserializer {
+    pairs: std::vec::IntoIter<(String, Vec<String>)>,
+    value: Option<Vec<String>>,
+}
+
+impl<'de> de::Deserializer<'de> for PairsDeserializer {
+    type Error = QueryError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        visitor.visit_map(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+impl<'de> de::MapAccess<'de> for PairsDeserializer {
+    type Error = QueryError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, QueryError> {
+        match self.pairs.next() {
+            Some((key, values)) => {
+                self.value = Some(values);
+                seed.deserialize(key.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, QueryError> {
+        let values = self.value.take().unwrap_or_default();
+        seed.deserialize(ValuesDeserializer(values))
+    }
+}
+
+/// The values of a key. Sequences get every value, other types the last one
+struct ValuesDeserializer(Vec<String>);
+
+impl ValuesDeserializer {
+    fn last(mut self) -> ValueDeserializer {
+        ValueDeserializer(self.0.pop().unwrap_or_default())
+    }
+}
+
+macro_rules! forward_to_last {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+                self.last().$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValuesDeserializer {
+    type Error = QueryError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        if self.0.len() > 1 {
+            self.deserialize_seq(visitor)
+        } else {
+            self.last().deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        visitor.visit_seq(de::value::SeqDeserializer::new(
+            self.0.into_iter().map(ValueDeserializer),
+        ))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        self.deserialize_seq(visitor)
+    }
+
+    /// An empty value, as sent for the empty inputs of HTML forms, is treated as missing
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        if self.0.iter().all(|value| value.is_empty()) {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        self.last().deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        self.last().deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        self.last().deserialize_struct(name, fields, visitor)
+    }
+
+    forward_to_last! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_f32
+        deserialize_f64 deserialize_char deserialize_str deserialize_string deserialize_bytes
+        deserialize_byte_buf deserialize_unit deserialize_map deserialize_identifier
+        deserialize_ignored_any
+    }
+}
+
+/// A single value, parsed according to the type it is deserialized into
+struct ValueDeserializer(String);
+
+impl<'de> IntoDeserializer<'de, QueryError> for ValueDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+                match self.0.trim().parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(e) => Err(de::Error::custom(format_args!("`{}`: {}", self.0, e))),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = QueryError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        visitor.visit_string(self.0)
+    }
+
+    /// Besides `true` and `false`, accepts the values sent by HTML checkboxes and flags without a
+    /// value, e.g. `?verbose`
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        match self.0.trim().to_ascii_lowercase().as_str() {
+            "" | "true" | "1" | "on" | "yes" => visitor.visit_bool(true),
+            "false" | "0" | "off" | "no" => visitor.visit_bool(false),
+            _ => Err(de::Error::invalid_value(
+                de::Unexpected::Str(&self.0),
+                &"a boolean",
+            )),
+        }
+    }
+
+    parse_value! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Order {
+        Asc,
+        Desc,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Search {
+        q: String,
+        page: u32,
+        score: Option<f64>,
+        exact: bool,
+        #[serde(default)]
+        tag: Vec<String>,
+        order: Option<Order>,
+    }
+
+    #[test]
+    fn splits_pairs() {
+        assert_eq!(
+            pairs("?a=1&b=x+y%2Fz&&flag&c=%E2%9C%93&bad=%zz"),
+            vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "x y/z".to_string()),
+                ("flag".to_string(), "".to_string()),
+                ("c".to_string(), "✓".to_string()),
+                ("bad".to_string(), "%zz".to_string()),
+            ]
+        );
+        assert!(pairs("").is_empty());
+    }
+
+    #[test]
+    fn deserializes_structs() {
+        let search: Search =
+            from_pairs(pairs("q=rust+wasm&page=2&tag=a&tag=b%20c&exact&order=desc")).unwrap();
+        assert_eq!(
+            search,
+            Search {
+                q: "rust wasm".to_string(),
+                page: 2,
+                score: None,
+                exact: true,
+                tag: vec!["a".to_string(), "b c".to_string()],
+                order: Some(Order::Desc),
+            }
+        );
+
+        let search: Search = from_pairs(pairs("q=&page=1&score=0.5&exact=off&order=")).unwrap();
+        assert_eq!(search.score, Some(0.5));
+        assert_eq!(search.order, None);
+        assert!(!search.exact);
+        assert!(search.tag.is_empty());
+    }
+
+    #[test]
+    fn reports_invalid_values() {
+        let err = from_pairs::<Search>(pairs("q=x&page=two&exact")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid query string: `two`: invalid digit found in string"
+        );
+        let err = from_pairs::<Search>(pairs("page=1&exact")).unwrap_err();
+        assert_eq!(err.to_string(), "invalid query string: missing field `q`");
+
+        let res = Response::from(err);
+        assert_eq!(res.status_code, 400);
+        assert_eq!(res.body, b"invalid query string: missing field `q`");
+    }
+}
@@ -0,0 +1,241 @@
+//! A fluent builder for responses, and shortcuts for common responses that carry a header
+
+use crate::{HeaderMap, Response, StatusCode};
+use serde::Serialize;
+
+/// Builds a [Response](struct.Response.html) from a status, headers and a body. Start one with
+/// [Response::builder](struct.Response.html#method.builder); the status defaults to `200 OK`.
+///
+/// The body methods finish the response. They set the `Content-Type` header, unless one was set
+/// with [header](#method.header):
+///
+/// ```
+/// # use wasmcloud_actor_http_server::{Response, StatusCode};
+/// let res = Response::builder()
+///     .status(StatusCode::Created)
+///     .header("Location", "/v1/users/7")
+///     .json(&7)
+///     .unwrap();
+/// assert_eq!(res.status, "Created");
+/// assert_eq!(res.header.get("Content-Type"), Some("application/json"));
+/// assert_eq!(res.body, b"7");
+///
+/// let res = Response::builder()
+///     .status(StatusCode::NotFound)
+///     .html("<h1>Not Found</h1>");
+/// assert_eq!(res.status_code, 404);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseBuilder {
+    status: StatusCode,
+    header: HeaderMap,
+}
+
+impl Default for ResponseBuilder {
+    fn default() -> Self {
+        ResponseBuilder {
+            status: StatusCode::Ok,
+            header: HeaderMap::new(),
+        }
+    }
+}
+
+impl ResponseBuilder {
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Adds a header, keeping any values it already has
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.header.append(name, value);
+        self
+    }
+
+    /// Finishes the response with a JSON body
+    pub fn json<T: Serialize + ?Sized>(self, payload: &T) -> serde_json::Result<Response> {
+        let body = serde_json::to_vec(payload)?;
+        Ok(self.body("application/json", body))
+    }
+
+    /// Finishes the response with a `text/plain` body
+    pub fn text(self, text: impl Into<String>) -> Response {
+        self.body("text/plain; charset=utf-8", text.into().into_bytes())
+    }
+
+    /// Finishes the response with a `text/html` body
+    pub fn html(self, html: impl Into<String>) -> Response {
+        self.body("text/html; charset=utf-8", html.into().into_bytes())
+    }
+
+    /// Finishes the response with an `application/octet-stream` body
+    pub fn bytes(self, bytes: impl Into<Vec<u8>>) -> Response {
+        self.body("application/octet-stream", bytes.into())
+    }
+
+    /// Finishes the response without a body
+    pub fn build(self) -> Response {
+        Response {
+            header: self.header,
+            ..Response::new(self.status)
+        }
+    }
+
+    fn body(mut self, content_type: &str, body: Vec<u8>) -> Response {
+        if !self.header.contains_key("Content-Type") {
+            self.header.insert("Content-Type", content_type);
+        }
+        Response {
+            body,
+            ..self.build()
+        }
+    }
+}
+
+impl From<ResponseBuilder> for Response {
+    fn from(builder: ResponseBuilder) -> Response {
+        builder.build()
+    }
+}
+
+impl Response {
+    /// Creates an empty response with the given status
+    pub fn new(status: StatusCode) -> Response {
+        Response {
+            status_code: status.code(),
+            status: status.reason_phrase().to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Starts building a response. See [ResponseBuilder](struct.ResponseBuilder.html)
+    pub fn builder() -> ResponseBuilder {
+        ResponseBuilder::default()
+    }
+
+    /// Creates a response with the given status and a `Location` header. Use one of the
+    /// redirection statuses, such as `SeeOther`
+    pub fn redirect(status: StatusCode, location: &str) -> Response {
+        Response::builder()
+            .status(status)
+            .header("Location", location)
+            .build()
+    }
+
+    /// Shortcut for creating a 301/Moved Permanently response
+    pub fn moved_permanently(location: &str) -> Response {
+        Response::redirect(StatusCode::MovedPermanently, location)
+    }
+
+    /// Shortcut for creating a 302/Found response
+    pub fn found(location: &str) -> Response {
+        Response::redirect(StatusCode::Found, location)
+    }
+
+    /// Shortcut for creating a 303/See Other response, which is usually sent after a form is
+    /// posted
+    pub fn see_other(location: &str) -> Response {
+        Response::redirect(StatusCode::SeeOther, location)
+    }
+
+    /// Shortcut for creating a 307/Temporary Redirect response, which, unlike `302`, keeps the
+    /// method of the request
+    pub fn temporary_redirect(location: &str) -> Response {
+        Response::redirect(StatusCode::TemporaryRedirect, location)
+    }
+
+    /// Shortcut for creating a 308/Permanent Redirect response, which, unlike `301`, keeps the
+    /// method of the request
+    pub fn permanent_redirect(location: &str) -> Response {
+        Response::redirect(StatusCode::PermanentRedirect, location)
+    }
+
+    /// Shortcut for creating a 201/Created response with the location of the new resource
+    pub fn created(location: &str) -> Response {
+        Response::builder()
+            .status(StatusCode::Created)
+            .header("Location", location)
+            .build()
+    }
+
+    /// Shortcut for creating a 204/No Content response
+    pub fn no_content() -> Response {
+        Response::new(StatusCode::NoContent)
+    }
+
+    /// Shortcut for creating a 401/Unauthorized response with the given challenge, such as
+    /// `Bearer realm="api"`, in its `WWW-Authenticate` header
+    pub fn unauthorized(challenge: &str) -> Response {
+        Response::builder()
+            .status(StatusCode::Unauthorized)
+            .header("WWW-Authenticate", challenge)
+            .build()
+    }
+
+    /// Shortcut for creating a 429/Too Many Requests response that asks the client to retry
+    /// after the given number of seconds
+    pub fn too_many_requests(retry_after_secs: u64) -> Response {
+        Response::builder()
+            .status(StatusCode::TooManyRequests)
+            .header("Retry-After", retry_after_secs.to_string())
+            .build()
+    }
+
+    /// Shortcut for creating a 503/Service Unavailable response
+    pub fn service_unavailable() -> Response {
+        Response::new(StatusCode::ServiceUnavailable)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builds_responses() {
+        let res = Response::builder()
+            .header("Content-Type", "application/problem+json")
+            .header("Vary", "Accept")
+            .header("Vary", "Origin")
+            .status(StatusCode::UnprocessableEntity)
+            .json(&["name"])
+            .unwrap();
+        assert_eq!(res.status_code, 422);
+        assert_eq!(res.status, "Unprocessable Entity");
+        assert_eq!(
+            res.header.get_all("Content-Type"),
+            ["application/problem+json"]
+        );
+        assert_eq!(res.header.get_all("Vary"), ["Accept", "Origin"]);
+        assert_eq!(res.body, br#"["name"]"#);
+
+        let res = Response::builder().text("hi");
+        assert_eq!(res.status_code, 200);
+        assert_eq!(res.header["Content-Type"], "text/plain; charset=utf-8");
+        let res = Response::builder().bytes(vec![0u8, 1]);
+        assert_eq!(res.header["Content-Type"], "application/octet-stream");
+        assert_eq!(Response::from(Response::builder()), Response::ok());
+    }
+
+    #[test]
+    fn creates_shortcut_responses() {
+        let res = Response::see_other("/done");
+        assert_eq!((res.status_code, res.status.as_str()), (303, "See Other"));
+        assert_eq!(res.header["Location"], "/done");
+        assert_eq!(Response::permanent_redirect("/b").status_code, 308);
+        assert_eq!(
+            Response::created("/v1/users/7").header["location"],
+            "/v1/users/7"
+        );
+        assert_eq!(Response::no_content().status_code, 204);
+        assert_eq!(
+            Response::unauthorized("Basic realm=\"admin\"").header["WWW-Authenticate"],
+            "Basic realm=\"admin\""
+        );
+        assert_eq!(Response::too_many_requests(30).header["Retry-After"], "30");
+        assert_eq!(
+            Response::service_unavailable().status,
+            "Service Unavailable"
+        );
+    }
+}
@@ -1,4 +1,8 @@
-use std::{str::FromStr, string::ParseError};
+use crate::Response;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
 /// Valid values for an HTTP method
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Method {
@@ -11,16 +15,37 @@ pub enum Method {
     Trace,
     Connect,
     Patch,
+    /// Any other method, such as the WebDAV `PROPFIND`. Extension methods are case-sensitive and
+    /// keep the case they were sent in
+    Extension(String),
+}
+
+impl Method {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Method::Options => "OPTIONS",
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Delete => "DELETE",
+            Method::Head => "HEAD",
+            Method::Trace => "TRACE",
+            Method::Connect => "CONNECT",
+            Method::Patch => "PATCH",
+            Method::Extension(method) => method,
+        }
+    }
 }
 
+/// Parses a method. The standard methods are recognized in any case, and any other valid token
+/// becomes an `Extension`
 impl FromStr for Method {
-    type Err = ParseError;
+    type Err = MethodError;
 
     fn from_str(input: &str) -> std::result::Result<Self, <Self as FromStr>::Err> {
-        let input = input.to_ascii_uppercase();
         let input = input.trim();
 
-        Ok(match input {
+        Ok(match input.to_ascii_uppercase().as_str() {
             "OPTIONS" => Method::Options,
             "GET" => Method::Get,
             "POST" => Method::Post,
@@ -30,7 +55,108 @@ impl FromStr for Method {
             "HEAD" => Method::Head,
             "CONNECT" => Method::Connect,
             "PATCH" => Method::Patch,
-            _ => Method::Get,
+            _ if is_token(input) => Method::Extension(input.to_string()),
+            _ => {
+                return Err(MethodError {
+                    method: input.to_string(),
+                })
+            }
         })
     }
 }
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl AsRef<str> for Method {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// Whether a method is a token as defined by RFC 7230
+fn is_token(method: &str) -> bool {
+    !method.is_empty()
+        && method
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// A method that is not a valid token. Converts into a `400 Bad Request`
+/// [Response](struct.Response.html)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodError {
+    pub method: String,
+}
+
+impl fmt::Display for MethodError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid HTTP method `{}`", self.method)
+    }
+}
+
+impl Error for MethodError {}
+
+impl From<MethodError> for Response {
+    fn from(e: MethodError) -> Response {
+        Response {
+            body: e.to_string().into_bytes(),
+            ..Response::bad_request()
+        }
+    }
+}
+
+/// Decodes the `%XX` escapes of a path segment. Invalid escapes are kept as they are and invalid UTF-8 is replaced
+pub(crate) fn percent_decode(input: &str) -> String {
+    fn hex(b: u8) -> Option<u8> {
+        match b {
+            b'0'..=b'9' => Some(b - b'0'),
+            b'a'..=b'f' => Some(b - b'a' + 10),
+            b'A'..=b'F' => Some(b - b'A' + 10),
+            _ => None,
+        }
+    }
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], bytes.get(i + 1), bytes.get(i + 2)) {
+            (b'%', Some(&h), Some(&l)) if hex(h).is_some() && hex(l).is_some() => {
+                out.push(hex(h).unwrap() << 4 | hex(l).unwrap());
+                i += 3;
+            }
+            (b, _, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_methods() {
+        assert_eq!(" get ".parse(), Ok(Method::Get));
+        assert_eq!(
+            "PROPFIND".parse::<Method>().unwrap(),
+            Method::Extension("PROPFIND".to_string())
+        );
+        assert_eq!(
+            Method::Extension("PropFind".to_string()).to_string(),
+            "PropFind"
+        );
+        assert_eq!(Method::Patch.as_ref(), "PATCH");
+
+        for invalid in &["", "GET /", "G\u{e9}T", "(GET)"] {
+            let err = invalid.parse::<Method>().unwrap_err();
+            assert_eq!(Response::from(err).status_code, 400);
+        }
+    }
+}
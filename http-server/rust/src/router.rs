@@ -0,0 +1,511 @@
+//! A declarative router that dispatches requests to handlers by method and path pattern
+
+use crate::body::BodyError;
+use crate::query::QueryError;
+use crate::route::{percent_decode, Method, MethodError};
+use crate::{HeaderMap, Request, Response, StatusCode};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use wapc_guest::HandlerResult;
+
+type Handler = Box<dyn Fn(Request, Params) -> HandlerResult<Response> + Send + Sync>;
+
+/// Dispatches requests to the handler of the first route whose method and pattern match them.
+///
+/// Patterns start with a `/` and are made of `/`-separated segments. A segment is either matched
+/// literally, or is a parameter such as `:id` that matches any single segment, or, as the last
+/// segment only, a wildcard such as `*rest` that matches the remaining segments, if any. Trailing
+/// slashes are ignored. The values of parameters and wildcards are percent-decoded and passed to
+/// the handler as [Params](struct.Params.html).
+///
+/// A request whose path matches no route is answered with `404 Not Found`. A request whose path
+/// matches routes for other methods only is answered with `405 Method Not Allowed`, with an
+/// `Allow` header listing those methods. A request with an extension method, such as `PROPFIND`,
+/// that no route of the router was added for is answered with `501 Not Implemented`, and one with
+/// an invalid method with `400 Bad Request`.
+///
+/// # Example
+/// ```
+/// use wasmcloud_actor_core as actor;
+/// use wasmcloud_actor_http_server::{Handlers, Params, Request, Response, Router};
+/// use wapc_guest::HandlerResult;
+///
+/// #[actor::init]
+/// fn init() {
+///     let users = Router::new()
+///         .get("/:id", get_user)
+///         .put("/:id", update_user)
+///         .get("/:id/files/*path", get_file);
+///     let router = Router::new()
+///         .get("/health", |_, _| Ok(Response::ok()))
+///         .nest("/v1/users", users);
+///     Handlers::register_handle_request(move |req| router.handle(req));
+/// }
+///
+/// fn get_user(_req: Request, params: Params) -> HandlerResult<Response> {
+///     let id: u64 = params.parse("id")?;
+///     Ok(Response::json(id, 200, "OK"))
+/// }
+///
+/// fn update_user(_req: Request, _params: Params) -> HandlerResult<Response> {
+///     Ok(Response::ok())
+/// }
+///
+/// fn get_file(_req: Request, params: Params) -> HandlerResult<Response> {
+///     Ok(Response::json(params.get("path"), 200, "OK"))
+/// }
+/// ```
+#[derive(Default)]
+pub struct Router {
+    routes: Vec<Route>,
+}
+
+struct Route {
+    /// The method of the route, or `None` for routes that match every method
+    method: Option<Method>,
+    pattern: Vec<Segment>,
+    handler: Handler,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Param(String),
+    Wildcard(String),
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Router::default()
+    }
+
+    /// Adds a route for requests with the given method
+    ///
+    /// # Panics
+    /// If the pattern doesn't start with `/`, a wildcard is not its last segment, or a parameter
+    /// or wildcard has no name
+    pub fn route<F>(mut self, method: Method, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request, Params) -> HandlerResult<Response> + Send + Sync + 'static,
+    {
+        self.routes.push(Route {
+            method: Some(method),
+            pattern: parse_pattern(pattern),
+            handler: Box::new(handler),
+        });
+        self
+    }
+
+    /// Adds a route for requests with any method, including extension methods
+    pub fn any<F>(mut self, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request, Params) -> HandlerResult<Response> + Send + Sync + 'static,
+    {
+        self.routes.push(Route {
+            method: None,
+            pattern: parse_pattern(pattern),
+            handler: Box::new(handler),
+        });
+        self
+    }
+
+    pub fn get<F>(self, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request, Params) -> HandlerResult<Response> + Send + Sync + 'static,
+    {
+        self.route(Method::Get, pattern, handler)
+    }
+
+    pub fn post<F>(self, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request, Params) -> HandlerResult<Response> + Send + Sync + 'static,
+    {
+        self.route(Method::Post, pattern, handler)
+    }
+
+    pub fn put<F>(self, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request, Params) -> HandlerResult<Response> + Send + Sync + 'static,
+    {
+        self.route(Method::Put, pattern, handler)
+    }
+
+    pub fn patch<F>(self, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request, Params) -> HandlerResult<Response> + Send + Sync + 'static,
+    {
+        self.route(Method::Patch, pattern, handler)
+    }
+
+    pub fn delete<F>(self, pattern: &str, handler: F) -> Self
+    where
+        F: Fn(Request, Params) -> HandlerResult<Response> + Send + Sync + 'static,
+    {
+        self.route(Method::Delete, pattern, handler)
+    }
+
+    /// Adds the routes of another router under a path prefix, which may itself contain
+    /// parameters
+    ///
+    /// # Panics
+    /// If the prefix doesn't start with `/` or contains a wildcard
+    pub fn nest(mut self, prefix: &str, router: Router) -> Self {
+        let prefix = parse_pattern(prefix);
+        if let Some(Segment::Wildcard(_)) = prefix.last() {
+            panic!("the prefix of a nested router can't contain a wildcard");
+        }
+        for route in router.routes {
+            let pattern = prefix.iter().cloned().chain(route.pattern).collect();
+            self.routes.push(Route { pattern, ..route });
+        }
+        self
+    }
+
+    /// Dispatches a request to the handler of the first matching route. A
+    /// [ParamError](struct.ParamError.html), [QueryError](struct.QueryError.html) or
+    /// [MethodError](struct.MethodError.html) returned by the handler is answered with
+    /// `400 Bad Request`, and a [BodyError](enum.BodyError.html) with
+    /// the response it converts into
+    pub fn handle(&self, req: Request) -> HandlerResult<Response> {
+        let method = match req.method() {
+            Ok(method) => method,
+            Err(e) => return Ok(e.into()),
+        };
+        let segments = req.path_segments();
+        let mut allowed = Vec::new();
+        for route in &self.routes {
+            let params = match match_pattern(&route.pattern, &segments) {
+                Some(params) => params,
+                None => continue,
+            };
+            match &route.method {
+                Some(m) if *m != method => {
+                    if !allowed.contains(m) {
+                        allowed.push(m.clone());
+                    }
+                }
+                _ => {
+                    return (route.handler)(req, params).or_else(error_response);
+                }
+            }
+        }
+        if let Method::Extension(_) = method {
+            if !self
+                .routes
+                .iter()
+                .any(|r| r.method.as_ref() == Some(&method))
+            {
+                return Ok(Response::new(StatusCode::NotImplemented));
+            }
+        }
+        if allowed.is_empty() {
+            return Ok(Response::not_found());
+        }
+        let allow = allowed.iter().map(Method::as_str).collect::<Vec<_>>();
+        let mut header = HeaderMap::new();
+        header.insert("Allow", allow.join(", "));
+        Ok(Response {
+            header,
+            ..Response::new(StatusCode::MethodNotAllowed)
+        })
+    }
+}
+
+/// Answers the errors caused by an invalid request instead of failing the invocation
+fn error_response(e: Box<dyn Error + Send + Sync>) -> HandlerResult<Response> {
+    if let Some(e) = e.downcast_ref::<QueryError>() {
+        return Ok(e.clone().into());
+    }
+    if let Some(e) = e.downcast_ref::<ParamError>() {
+        return Ok(e.clone().into());
+    }
+    if let Some(e) = e.downcast_ref::<BodyError>() {
+        return Ok(e.clone().into());
+    }
+    if let Some(e) = e.downcast_ref::<MethodError>() {
+        return Ok(e.clone().into());
+    }
+    Err(e)
+}
+
+fn parse_pattern(pattern: &str) -> Vec<Segment> {
+    assert!(
+        pattern.starts_with('/'),
+        "the pattern `{}` must start with `/`",
+        pattern
+    );
+    let segments: Vec<Segment> = pattern
+        .trim_end_matches('/')
+        .split('/')
+        .skip(1)
+        .map(|s| {
+            if let Some(name) = s.strip_prefix(':') {
+                assert!(!name.is_empty(), "unnamed parameter in `{}`", pattern);
+                Segment::Param(name.to_string())
+            } else if let Some(name) = s.strip_prefix('*') {
+                assert!(!name.is_empty(), "unnamed wildcard in `{}`", pattern);
+                Segment::Wildcard(name.to_string())
+            } else {
+                Segment::Literal(s.to_string())
+            }
+        })
+        .collect();
+    if let Some(i) = segments
+        .iter()
+        .position(|s| matches!(s, Segment::Wildcard(_)))
+    {
+        assert!(
+            i == segments.len() - 1,
+            "the wildcard in `{}` must be its last segment",
+            pattern
+        );
+    }
+    segments
+}
+
+fn match_pattern(pattern: &[Segment], segments: &[&str]) -> Option<Params> {
+    let mut params = Params::default();
+    for (i, segment) in pattern.iter().enumerate() {
+        match segment {
+            Segment::Wildcard(name) => {
+                let rest = segments.get(i..).unwrap_or_default();
+                params
+                    .values
+                    .push((name.clone(), percent_decode(&rest.join("/"))));
+                return Some(params);
+            }
+            Segment::Param(name) => {
+                params
+                    .values
+                    .push((name.clone(), percent_decode(segments.get(i)?)));
+            }
+            Segment::Literal(literal) => {
+                if segments.get(i)? != literal {
+                    return None;
+                }
+            }
+        }
+    }
+    if segments.len() == pattern.len() {
+        Some(params)
+    } else {
+        None
+    }
+}
+
+/// The values of the parameters and wildcards of the route that matched a request
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Params {
+    values: Vec<(String, String)>,
+}
+
+impl Params {
+    /// The percent-decoded value of a parameter or wildcard
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Parses the value of a parameter or wildcard, e.g. `let id: u64 = params.parse("id")?;`
+    pub fn parse<T: FromStr>(&self, name: &str) -> Result<T, ParamError> {
+        let value = self.get(name).ok_or_else(|| ParamError {
+            name: name.to_string(),
+            value: None,
+        })?;
+        value.parse().map_err(|_| ParamError {
+            name: name.to_string(),
+            value: Some(value.to_string()),
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+}
+
+/// A path parameter that is missing from the route or couldn't be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamError {
+    pub name: String,
+    /// The value that couldn't be parsed, or `None` if the route has no such parameter
+    pub value: Option<String>,
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "invalid path parameter `{}`: `{}`", self.name, value),
+            None => write!(f, "missing path parameter `{}`", self.name),
+        }
+    }
+}
+
+impl Error for ParamError {}
+
+impl From<ParamError> for Response {
+    fn from(e: ParamError) -> Response {
+        Response {
+            body: e.to_string().into_bytes(),
+            ..Response::bad_request()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn request(method: &str, path: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn echo(req: Request, params: Params) -> HandlerResult<Response> {
+        let params: Vec<String> = params.iter().map(|(n, v)| format!("{}={}", n, v)).collect();
+        Ok(Response {
+            body: format!("{} {}", req.method, params.join(" ")).into_bytes(),
+            ..Response::ok()
+        })
+    }
+
+    fn body(res: HandlerResult<Response>) -> String {
+        String::from_utf8(res.unwrap().body).unwrap()
+    }
+
+    #[test]
+    fn matches_patterns() {
+        let router = Router::new()
+            .get("/users", echo)
+            .get("/users/:id", echo)
+            .get("/users/:id/files/*path", echo)
+            .any("/echo/", echo);
+
+        assert_eq!(body(router.handle(request("GET", "/users/"))), "GET ");
+        assert_eq!(
+            body(router.handle(request("GET", "/users/a%20b"))),
+            "GET id=a b"
+        );
+        assert_eq!(
+            body(router.handle(request("GET", "/users/7/files/docs/a.txt"))),
+            "GET id=7 path=docs/a.txt"
+        );
+        assert_eq!(
+            body(router.handle(request("GET", "/users/7/files"))),
+            "GET id=7 path="
+        );
+        assert_eq!(body(router.handle(request("DELETE", "/echo"))), "DELETE ");
+
+        let res = router.handle(request("GET", "/users/7/avatar")).unwrap();
+        assert_eq!(res.status_code, 404);
+    }
+
+    #[test]
+    fn rejects_other_methods() {
+        let router = Router::new()
+            .get("/users/:id", echo)
+            .put("/users/:id", echo)
+            .get("/users/:id", echo);
+
+        let res = router.handle(request("POST", "/users/7")).unwrap();
+        assert_eq!(res.status_code, 405);
+        assert_eq!(res.header["Allow"], "GET, PUT");
+    }
+
+    #[test]
+    fn never_runs_other_handlers_for_unknown_methods() {
+        let propfind = Method::Extension("PROPFIND".to_string());
+        let router = Router::new()
+            .get("/files/*path", echo)
+            .route(propfind, "/dav/*path", echo);
+
+        let res = router.handle(request("BREW", "/files/a.txt")).unwrap();
+        assert_eq!(res.status_code, 501);
+        let res = router.handle(request("PROPFIND", "/files/a.txt")).unwrap();
+        assert_eq!(res.status_code, 405);
+        assert_eq!(res.header["Allow"], "GET");
+        assert_eq!(
+            body(router.handle(request("PROPFIND", "/dav/a.txt"))),
+            "PROPFIND path=a.txt"
+        );
+        let res = router.handle(request("GET /", "/files/a.txt")).unwrap();
+        assert_eq!(res.status_code, 400);
+    }
+
+    #[test]
+    fn nests_routers() {
+        let users = Router::new().get("/", echo).get("/:id", echo);
+        let router = Router::new()
+            .nest("/v1/:tenant/users", users)
+            .get("/health", echo);
+
+        assert_eq!(
+            body(router.handle(request("GET", "/v1/acme/users"))),
+            "GET tenant=acme"
+        );
+        assert_eq!(
+            body(router.handle(request("GET", "/v1/acme/users/7"))),
+            "GET tenant=acme id=7"
+        );
+        assert_eq!(body(router.handle(request("GET", "/health"))), "GET ");
+    }
+
+    #[test]
+    fn parses_params() {
+        let router = Router::new().get("/users/:id", |_, params| {
+            let id: u64 = params.parse("id")?;
+            Ok(Response::json(id, 200, "OK"))
+        });
+
+        assert_eq!(body(router.handle(request("GET", "/users/7"))), "7");
+        let res = router.handle(request("GET", "/users/seven")).unwrap();
+        assert_eq!(res.status_code, 400);
+        assert_eq!(res.body, b"invalid path parameter `id`: `seven`");
+    }
+
+    #[test]
+    fn answers_invalid_queries() {
+        let router = Router::new().get("/users", |req, _| {
+            let limit: std::collections::HashMap<String, u32> = req.query()?;
+            Ok(Response::json(limit, 200, "OK"))
+        });
+
+        assert_eq!(
+            body(router.handle(Request {
+                query_string: "limit=10".to_string(),
+                ..request("GET", "/users")
+            })),
+            r#"{"limit":10}"#
+        );
+        let res = router
+            .handle(Request {
+                query_string: "limit=ten".to_string(),
+                ..request("GET", "/users")
+            })
+            .unwrap();
+        assert_eq!(res.status_code, 400);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be its last segment")]
+    fn rejects_misplaced_wildcards() {
+        Router::new().get("/files/*path/raw", echo);
+    }
+
+    #[test]
+    #[should_panic(expected = "the pattern `users/:id` must start with `/`")]
+    fn rejects_relative_patterns() {
+        Router::new().get("users/:id", echo);
+    }
+
+    #[test]
+    #[should_panic(expected = "the pattern `v1` must start with `/`")]
+    fn rejects_relative_prefixes() {
+        Router::new().nest("v1", Router::new().get("/", echo));
+    }
+}
@@ -0,0 +1,167 @@
+//! HTTP status codes and their reason phrases
+
+use std::fmt;
+
+macro_rules! status_codes {
+    ($($name:ident = $code:expr, $reason:expr;)*) => {
+        /// The status codes registered with IANA, each with its canonical reason phrase
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum StatusCode {
+            $(
+                #[doc = concat!("`", stringify!($code), " ", $reason, "`")]
+                $name,
+            )*
+        }
+
+        impl StatusCode {
+            /// The numeric code, as carried by the `status_code` of a
+            /// [Response](struct.Response.html)
+            pub fn code(self) -> u32 {
+                match self {
+                    $(StatusCode::$name => $code,)*
+                }
+            }
+
+            /// The canonical reason phrase, as carried by the `status` of a
+            /// [Response](struct.Response.html)
+            pub fn reason_phrase(self) -> &'static str {
+                match self {
+                    $(StatusCode::$name => $reason,)*
+                }
+            }
+
+            /// The status with the given code, if it is registered
+            pub fn from_code(code: u32) -> Option<StatusCode> {
+                match code {
+                    $($code => Some(StatusCode::$name),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+status_codes! {
+    Continue = 100, "Continue";
+    SwitchingProtocols = 101, "Switching Protocols";
+    Processing = 102, "Processing";
+    EarlyHints = 103, "Early Hints";
+    Ok = 200, "OK";
+    Created = 201, "Created";
+    Accepted = 202, "Accepted";
+    NonAuthoritativeInformation = 203, "Non-Authoritative Information";
+    NoContent = 204, "No Content";
+    ResetContent = 205, "Reset Content";
+    PartialContent = 206, "Partial Content";
+    MultiStatus = 207, "Multi-Status";
+    AlreadyReported = 208, "Already Reported";
+    ImUsed = 226, "IM Used";
+    MultipleChoices = 300, "Multiple Choices";
+    MovedPermanently = 301, "Moved Permanently";
+    Found = 302, "Found";
+    SeeOther = 303, "See Other";
+    NotModified = 304, "Not Modified";
+    UseProxy = 305, "Use Proxy";
+    TemporaryRedirect = 307, "Temporary Redirect";
+    PermanentRedirect = 308, "Permanent Redirect";
+    BadRequest = 400, "Bad Request";
+    Unauthorized = 401, "Unauthorized";
+    PaymentRequired = 402, "Payment Required";
+    Forbidden = 403, "Forbidden";
+    NotFound = 404, "Not Found";
+    MethodNotAllowed = 405, "Method Not Allowed";
+    NotAcceptable = 406, "Not Acceptable";
+    ProxyAuthenticationRequired = 407, "Proxy Authentication Required";
+    RequestTimeout = 408, "Request Timeout";
+    Conflict = 409, "Conflict";
+    Gone = 410, "Gone";
+    LengthRequired = 411, "Length Required";
+    PreconditionFailed = 412, "Precondition Failed";
+    PayloadTooLarge = 413, "Payload Too Large";
+    UriTooLong = 414, "URI Too Long";
+    UnsupportedMediaType = 415, "Unsupported Media Type";
+    RangeNotSatisfiable = 416, "Range Not Satisfiable";
+    ExpectationFailed = 417, "Expectation Failed";
+    MisdirectedRequest = 421, "Misdirected Request";
+    UnprocessableEntity = 422, "Unprocessable Entity";
+    Locked = 423, "Locked";
+    FailedDependency = 424, "Failed Dependency";
+    TooEarly = 425, "Too Early";
+    UpgradeRequired = 426, "Upgrade Required";
+    PreconditionRequired = 428, "Precondition Required";
+    TooManyRequests = 429, "Too Many Requests";
+    RequestHeaderFieldsTooLarge = 431, "Request Header Fields Too Large";
+    UnavailableForLegalReasons = 451, "Unavailable For Legal Reasons";
+    InternalServerError = 500, "Internal Server Error";
+    NotImplemented = 501, "Not Implemented";
+    BadGateway = 502, "Bad Gateway";
+    ServiceUnavailable = 503, "Service Unavailable";
+    GatewayTimeout = 504, "Gateway Timeout";
+    HttpVersionNotSupported = 505, "HTTP Version Not Supported";
+    VariantAlsoNegotiates = 506, "Variant Also Negotiates";
+    InsufficientStorage = 507, "Insufficient Storage";
+    LoopDetected = 508, "Loop Detected";
+    NotExtended = 510, "Not Extended";
+    NetworkAuthenticationRequired = 511, "Network Authentication Required";
+}
+
+impl StatusCode {
+    /// `1xx` codes
+    pub fn is_informational(self) -> bool {
+        (100..200).contains(&self.code())
+    }
+
+    /// `2xx` codes
+    pub fn is_success(self) -> bool {
+        (200..300).contains(&self.code())
+    }
+
+    /// `3xx` codes
+    pub fn is_redirection(self) -> bool {
+        (300..400).contains(&self.code())
+    }
+
+    /// `4xx` codes
+    pub fn is_client_error(self) -> bool {
+        (400..500).contains(&self.code())
+    }
+
+    /// `5xx` codes
+    pub fn is_server_error(self) -> bool {
+        (500..600).contains(&self.code())
+    }
+}
+
+/// Formats the code followed by the reason phrase, e.g. `404 Not Found`
+impl fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.code(), self.reason_phrase())
+    }
+}
+
+impl From<StatusCode> for u32 {
+    fn from(status: StatusCode) -> u32 {
+        status.code()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn maps_codes_and_reasons() {
+        assert_eq!(StatusCode::from_code(418), None);
+        for code in 100..600 {
+            if let Some(status) = StatusCode::from_code(code) {
+                assert_eq!(status.code(), code);
+            }
+        }
+        let status = StatusCode::from_code(429).unwrap();
+        assert_eq!(status, StatusCode::TooManyRequests);
+        assert_eq!(status.to_string(), "429 Too Many Requests");
+        assert!(status.is_client_error());
+        assert!(!StatusCode::PermanentRedirect.is_success());
+        assert!(StatusCode::PermanentRedirect.is_redirection());
+    }
+}
@@ -0,0 +1,7 @@
+fn main() {
+    wasmcloud_widl_codegen::Generator::new("keyvalue.widl")
+        .host_role("Store")
+        .provider_role("Store", "KeyValueStore")
+        .build()
+        .unwrap();
+}
@@ -18,8 +18,15 @@
 //! }
 //! ```
 //!
+//! # Capability providers
+//! With the `provider` feature enabled, this crate also exposes the provider side of the contract: the
+//! [KeyValueStore](trait.KeyValueStore.html) trait, with one method per operation, and a [dispatch](fn.dispatch.html)
+//! function that decodes the arguments of an actor's call, invokes the matching trait method and encodes its reply.
+//!
 
-mod generated;
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+}
 
 pub use generated::*;
 
@@ -38,3 +45,156 @@ pub const OP_SET_UNION: &str = "SetUnion";
 pub const OP_SET_INTERSECT: &str = "SetIntersection";
 pub const OP_SET_QUERY: &str = "SetQuery";
 pub const OP_KEY_EXISTS: &str = "KeyExists";
+
+#[cfg(test)]
+#[cfg(feature = "guest")]
+mod host {
+    use wasmcloud_actor_core::testing;
+
+    #[test]
+    fn malformed_replies_are_errors() {
+        testing::expect_raw("default", "wasmcloud:keyvalue", "Get", |_| {
+            Ok(vec![0xc1, 0x00])
+        });
+        let err = crate::default().get("counter".to_string()).unwrap_err();
+        assert!(err.to_string().starts_with(
+            "internal: failed to decode the reply to `Get` from `wasmcloud:keyvalue` as `GetResponse`"
+        ));
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "provider")]
+mod test {
+    use super::*;
+    use std::error::Error;
+
+    struct Store;
+
+    impl KeyValueStore for Store {
+        fn get(
+            &self,
+            actor: &str,
+            key: String,
+        ) -> Result<GetResponse, Box<dyn Error + Send + Sync>> {
+            Ok(GetResponse {
+                value: format!("{}/{}", actor, key),
+                exists: true,
+            })
+        }
+        fn add(
+            &self,
+            _: &str,
+            _: String,
+            value: i32,
+        ) -> Result<AddResponse, Box<dyn Error + Send + Sync>> {
+            Ok(AddResponse { value })
+        }
+        fn set(
+            &self,
+            _: &str,
+            _: String,
+            value: String,
+            _: i32,
+        ) -> Result<SetResponse, Box<dyn Error + Send + Sync>> {
+            Ok(SetResponse { value })
+        }
+        fn del(&self, _: &str, key: String) -> Result<DelResponse, Box<dyn Error + Send + Sync>> {
+            Err(format!("cannot delete {}", key).into())
+        }
+        fn clear(&self, _: &str, key: String) -> Result<DelResponse, Box<dyn Error + Send + Sync>> {
+            Ok(DelResponse { key })
+        }
+        fn range(
+            &self,
+            _: &str,
+            _: String,
+            _: i32,
+            _: i32,
+        ) -> Result<ListRangeResponse, Box<dyn Error + Send + Sync>> {
+            Ok(ListRangeResponse::default())
+        }
+        fn push(
+            &self,
+            _: &str,
+            _: String,
+            _: String,
+        ) -> Result<ListResponse, Box<dyn Error + Send + Sync>> {
+            Ok(ListResponse::default())
+        }
+        fn list_item_delete(
+            &self,
+            _: &str,
+            _: String,
+            _: String,
+        ) -> Result<ListResponse, Box<dyn Error + Send + Sync>> {
+            Ok(ListResponse::default())
+        }
+        fn set_add(
+            &self,
+            _: &str,
+            _: String,
+            _: String,
+        ) -> Result<SetOperationResponse, Box<dyn Error + Send + Sync>> {
+            Ok(SetOperationResponse::default())
+        }
+        fn set_remove(
+            &self,
+            _: &str,
+            _: String,
+            _: String,
+        ) -> Result<SetOperationResponse, Box<dyn Error + Send + Sync>> {
+            Ok(SetOperationResponse::default())
+        }
+        fn set_union(
+            &self,
+            _: &str,
+            _: Vec<String>,
+        ) -> Result<SetQueryResponse, Box<dyn Error + Send + Sync>> {
+            Ok(SetQueryResponse::default())
+        }
+        fn set_intersection(
+            &self,
+            _: &str,
+            _: Vec<String>,
+        ) -> Result<SetQueryResponse, Box<dyn Error + Send + Sync>> {
+            Ok(SetQueryResponse::default())
+        }
+        fn set_query(
+            &self,
+            _: &str,
+            _: String,
+        ) -> Result<SetQueryResponse, Box<dyn Error + Send + Sync>> {
+            Ok(SetQueryResponse::default())
+        }
+        fn key_exists(
+            &self,
+            _: &str,
+            _: String,
+        ) -> Result<GetResponse, Box<dyn Error + Send + Sync>> {
+            Ok(GetResponse::default())
+        }
+    }
+
+    #[test]
+    fn dispatches_operations() {
+        let args = serialize(GetArgs {
+            key: "counter".to_string(),
+        })
+        .unwrap();
+        let reply = dispatch(&Store, "Mxyz", OP_GET, &args).unwrap();
+        let resp: GetResponse = deserialize(&reply).unwrap();
+        assert_eq!(resp.value, "Mxyz/counter");
+
+        let args = serialize(DelArgs {
+            key: "counter".to_string(),
+        })
+        .unwrap();
+        let err = dispatch(&Store, "Mxyz", OP_DEL, &args).unwrap_err();
+        assert_eq!(err.to_string(), "cannot delete counter");
+
+        let err = dispatch(&Store, "Mxyz", "Frobnicate", &[]).unwrap_err();
+        let err = wasmcloud_actor_core::ActorError::from(err);
+        assert_eq!(err.kind(), wasmcloud_actor_core::ErrorKind::InvalidArgument);
+    }
+}
@@ -0,0 +1,9 @@
+fn main() {
+    wasmcloud_widl_codegen::Generator::new("logging.widl")
+        .host_role("Logger")
+        .provider_role("Logger", "Logger")
+        .custom_host()
+        .internal_operation("WriteLog")
+        .build()
+        .unwrap();
+}
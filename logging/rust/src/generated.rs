@@ -1,96 +0,0 @@
-extern crate rmp_serde as rmps;
-use rmps::{Deserializer, Serializer};
-use serde::{Deserialize, Serialize};
-use std::io::Cursor;
-
-#[cfg(feature = "guest")]
-extern crate wapc_guest as guest;
-#[cfg(feature = "guest")]
-use crate::{set_binding, CURRENT_BINDING};
-#[cfg(feature = "guest")]
-use guest::prelude::*;
-
-#[cfg(feature = "guest")]
-pub struct Host {}
-
-#[cfg(feature = "guest")]
-impl Default for Host {
-    fn default() -> Self {
-        set_binding("default");
-        Host {}
-    }
-}
-
-/// Creates a named host binding
-#[cfg(feature = "guest")]
-pub fn host(binding: &str) -> Host {
-    set_binding(binding);
-    Host {}
-}
-
-/// Creates the default host binding
-#[cfg(feature = "guest")]
-pub fn default() -> Host {
-    Host::default()
-}
-
-#[cfg(feature = "guest")]
-impl Host {
-    pub(crate) fn _write_log(
-        &self,
-        target: String,
-        level: String,
-        text: String,
-    ) -> HandlerResult<()> {
-        let input_args = WriteLogArgs {
-            target,
-            level,
-            text,
-        };
-        host_call(
-            &CURRENT_BINDING.read().unwrap(),
-            "wasmcloud:logging",
-            "WriteLog",
-            &serialize(input_args)?,
-        )
-        .map(|_vec| ())
-        .map_err(|e| e.into())
-    }
-}
-
-#[derive(Debug, PartialEq, Deserialize, Serialize, Default, Clone)]
-pub struct WriteLogArgs {
-    #[serde(rename = "target")]
-    pub target: String,
-    #[serde(rename = "level")]
-    pub level: String,
-    #[serde(rename = "text")]
-    pub text: String,
-}
-
-/// The standard function for serializing codec structs into a format that can be
-/// used for message exchange between actor and host. Use of any other function to
-/// serialize could result in breaking incompatibilities.
-pub fn serialize<T>(
-    item: T,
-) -> ::std::result::Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>
-where
-    T: Serialize,
-{
-    let mut buf = Vec::new();
-    item.serialize(&mut Serializer::new(&mut buf).with_struct_map())?;
-    Ok(buf)
-}
-
-/// The standard function for de-serializing codec structs from a format suitable
-/// for message exchange between actor and host. Use of any other function to
-/// deserialize could result in breaking incompatibilities.
-pub fn deserialize<'de, T: Deserialize<'de>>(
-    buf: &[u8],
-) -> ::std::result::Result<T, Box<dyn std::error::Error + Send + Sync>> {
-    let mut de = Deserializer::new(Cursor::new(buf));
-    match Deserialize::deserialize(&mut de) {
-        Ok(t) => Ok(t),
-        Err(e) => Err(format!("Failed to de-serialize: {}", e).into()),
-    }
-}
@@ -38,8 +38,17 @@
 //!     Ok(http::Response::ok())
 //! }
 //! ```
+//!
+//! While the actor handles an invocation, every log line ends with the `trace_id` and `span_id` of its trace
+//! context (see `wasmcloud_actor_core::trace`), so the lines can be correlated with the calls that produced them.
+//!
+//! # Capability providers
+//! The `provider` feature exposes the [Logger](trait.Logger.html) trait, which capability providers implement for
+//! writing log messages, and a [dispatch](fn.dispatch.html) function that routes an actor's call to it.
 
-mod generated;
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+}
 #[allow(unused_imports)]
 pub use generated::*;
 
@@ -50,8 +59,38 @@ pub const OP_LOG: &str = "WriteLog";
 #[doc(hidden)]
 static LOG_LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
 
+/// The host binding used by the logger. Creating a binding with [host](fn.host.html) or
+/// [default](fn.default.html) also selects it for the standard log macros
+#[cfg(feature = "guest")]
+pub struct Host {}
+
+#[cfg(feature = "guest")]
+impl Default for Host {
+    fn default() -> Self {
+        set_binding("default");
+        Host {}
+    }
+}
+
+/// Creates a named host binding
+#[cfg(feature = "guest")]
+pub fn host(binding: &str) -> Host {
+    set_binding(binding);
+    Host {}
+}
+
+/// Creates the default host binding
+#[cfg(feature = "guest")]
+pub fn default() -> Host {
+    Host::default()
+}
+
 #[cfg(feature = "guest")]
 impl Host {
+    fn binding(&self) -> String {
+        CURRENT_BINDING.read().unwrap().clone()
+    }
+
     /// Writes a log message to specified target and level
     ///
     /// # Arguments
@@ -66,7 +105,21 @@ impl Host {
         } else {
             "info"
         };
-        self._write_log(target.to_string(), log_level.to_string(), text.to_string())
+        self._write_log(target.to_string(), log_level.to_string(), with_trace(text))
+    }
+}
+
+/// Appends the ids of the current trace context, if any, to a log line
+#[cfg(feature = "guest")]
+fn with_trace(text: &str) -> String {
+    match wasmcloud_actor_core::trace::current() {
+        Some(ctx) => format!(
+            "{} trace_id={} span_id={}",
+            text,
+            ctx.trace_id_hex(),
+            ctx.span_id_hex()
+        ),
+        None => text.to_string(),
     }
 }
 
@@ -122,9 +175,44 @@ impl log::Log for Host {
         let _ = self._write_log(
             record.target().to_string(),
             level.to_string(),
-            format!("{}", record.args()),
+            with_trace(&record.args().to_string()),
         );
     }
 
     fn flush(&self) {}
 }
+
+#[cfg(test)]
+#[cfg(feature = "guest")]
+mod test {
+    use crate::{deserialize, WriteLogArgs};
+    use wasmcloud_actor_core::{testing, trace};
+
+    #[test]
+    fn log_lines_carry_trace_ids() {
+        testing::expect(
+            "default",
+            "wasmcloud:logging",
+            "WriteLog",
+            |_: WriteLogArgs| Ok(()),
+        );
+        let ctx = trace::TraceContext::root();
+        trace::set_current(Some(ctx));
+        crate::default().write_log("", "info", "hello").unwrap();
+        trace::set_current(None);
+        crate::default().write_log("", "info", "bye").unwrap();
+
+        let calls = testing::calls();
+        let args: WriteLogArgs = deserialize(&calls[0].payload).unwrap();
+        assert_eq!(
+            args.text,
+            format!(
+                "hello trace_id={} span_id={}",
+                ctx.trace_id_hex(),
+                ctx.span_id_hex()
+            )
+        );
+        let args: WriteLogArgs = deserialize(&calls[1].payload).unwrap();
+        assert_eq!(args.text, "bye");
+    }
+}
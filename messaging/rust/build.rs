@@ -0,0 +1,14 @@
+fn main() {
+    wasmcloud_widl_codegen::Generator::new("messaging.widl")
+        .host_role("Broker")
+        .provider_role("Broker", "MessageBroker")
+        .handler_role("Consumer")
+        .borrowed_type("BrokerMessage")
+        .internal_operation("Publish")
+        .internal_operation("Request")
+        .trace_operation("Publish")
+        .trace_operation("Request")
+        .trace_operation("HandleMessage")
+        .build()
+        .unwrap();
+}
@@ -26,8 +26,316 @@
 //! }
 //!
 //! ```
+//!
+//! Handlers can also be closures, which lets them capture configuration or other state:
+//!
+//! ```rust
+//! # extern crate wasmcloud_actor_messaging as messaging;
+//! let reply = "pong".to_string();
+//! messaging::Handlers::register_handle_message(move |message| {
+//!     messaging::default().publish(message.reply_to, "".to_string(), reply.clone().into_bytes())?;
+//!     Ok(())
+//! });
+//! ```
+//!
+//! The trace context of an incoming message is taken from its `traceparent` header, when it has one, and the
+//! trace context of the current invocation is added to the headers of published messages and requests. Use
+//! `publish_with_headers` and `request_with_headers` to send other headers along. See `wasmcloud_actor_core::trace`.
+//!
+//! Actors that handle large messages can register with `register_handle_message_ref` instead, and receive a
+//! [BrokerMessageRef](struct.BrokerMessageRef.html) whose body borrows from the payload delivered by the host
+//! rather than being copied out of it.
+//!
+//! # Capability providers
+//! The `provider` feature exposes the [MessageBroker](trait.MessageBroker.html) trait, which capability providers
+//! implement for publishing messages and making requests, and a [dispatch](fn.dispatch.html) function that routes
+//! an actor's call to it.
 
-mod generated;
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+}
 pub use generated::*;
 
+#[cfg(feature = "guest")]
+use std::collections::HashMap;
+#[cfg(feature = "guest")]
+use wapc_guest::HandlerResult;
+use wasmcloud_actor_core::trace::Carrier;
+
 pub const OP_HANDLE_MESSAGE: &str = "HandleMessage";
+
+#[cfg(feature = "guest")]
+impl Host {
+    /// Publish a message on a given subject. If a reply is not expected, the value can
+    /// be left as an empty string. The message carries the trace context of the current
+    /// invocation in a `traceparent` header.
+    pub fn publish(
+        &self,
+        subject: String,
+        reply_to: String,
+        body: Vec<u8>,
+    ) -> HandlerResult<PublishResponse> {
+        self._publish(subject, reply_to, body, None)
+    }
+
+    /// Publish a message with headers on a given subject. The trace context of the current
+    /// invocation is added to the headers, replacing any `traceparent` header given.
+    pub fn publish_with_headers(
+        &self,
+        subject: String,
+        reply_to: String,
+        body: Vec<u8>,
+        headers: HashMap<String, String>,
+    ) -> HandlerResult<PublishResponse> {
+        self._publish(subject, reply_to, body, Some(headers))
+    }
+
+    /// Request a response on a given subject in a given timeout. The request carries the trace
+    /// context of the current invocation in a `traceparent` header.
+    pub fn request(
+        &self,
+        subject: String,
+        body: Vec<u8>,
+        timeout: i64,
+    ) -> HandlerResult<BrokerMessage> {
+        self._request(subject, body, timeout, None)
+    }
+
+    /// Request a response on a given subject in a given timeout, sending headers with the
+    /// request. The trace context of the current invocation is added to the headers, replacing
+    /// any `traceparent` header given.
+    pub fn request_with_headers(
+        &self,
+        subject: String,
+        body: Vec<u8>,
+        timeout: i64,
+        headers: HashMap<String, String>,
+    ) -> HandlerResult<BrokerMessage> {
+        self._request(subject, body, timeout, Some(headers))
+    }
+}
+
+/// Carries the trace context of a message in its headers
+impl Carrier for BrokerMessage {
+    fn get(&self, key: &str) -> Option<&str> {
+        Carrier::get(&self.headers, key)
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        Carrier::set(&mut self.headers, key, value)
+    }
+}
+
+/// Carries the trace context of a message in its headers
+impl<'a> Carrier for BrokerMessageRef<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        Carrier::get(&self.headers, key)
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        Carrier::set(&mut self.headers, key, value)
+    }
+}
+
+/// Carries the trace context of a published message in its headers
+impl Carrier for PublishArgs {
+    fn get(&self, key: &str) -> Option<&str> {
+        Carrier::get(&self.headers, key)
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        Carrier::set(&mut self.headers, key, value)
+    }
+}
+
+/// Carries the trace context of a request in its headers
+impl Carrier for RequestArgs {
+    fn get(&self, key: &str) -> Option<&str> {
+        Carrier::get(&self.headers, key)
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        Carrier::set(&mut self.headers, key, value)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "guest")]
+mod test {
+    use crate::{deserialize, BrokerMessage, Handlers, PublishArgs, RequestArgs};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, MutexGuard};
+    use wasmcloud_actor_core::{testing, trace};
+
+    /// Handlers are registered for the whole process, so the tests that register one for
+    /// `HandleMessage` take turns
+    pub(crate) fn handle_message_lock() -> MutexGuard<'static, ()> {
+        static LOCK: Mutex<()> = Mutex::new(());
+        LOCK.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[test]
+    fn closures_capture_state() {
+        let _lock = handle_message_lock();
+        let received = Arc::new(AtomicUsize::new(0));
+        let counter = received.clone();
+        Handlers::register_handle_message(move |msg: BrokerMessage| {
+            counter.fetch_add(msg.body.len(), Ordering::SeqCst);
+            Ok(())
+        });
+
+        let msg = BrokerMessage {
+            subject: "events".to_string(),
+            reply_to: "".to_string(),
+            body: b"hello".to_vec(),
+            headers: None,
+        };
+        testing::invoke::<_, ()>("HandleMessage", msg).unwrap();
+        assert_eq!(received.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn continues_the_trace_of_messages() {
+        let _lock = handle_message_lock();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        Handlers::register_handle_message(move |msg: BrokerMessage| {
+            log.lock()
+                .unwrap()
+                .push((msg.body, trace::current().unwrap()));
+            Ok(())
+        });
+
+        let mut headers = HashMap::new();
+        headers.insert(
+            "traceparent".to_string(),
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01".to_string(),
+        );
+        let msg = BrokerMessage {
+            subject: "events".to_string(),
+            reply_to: "".to_string(),
+            body: b"hello".to_vec(),
+            headers: Some(headers),
+        };
+        testing::invoke::<_, ()>("HandleMessage", &msg).unwrap();
+        testing::invoke::<_, ()>(
+            "HandleMessage",
+            BrokerMessage {
+                headers: None,
+                ..msg
+            },
+        )
+        .unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].0, b"hello");
+        assert_eq!(received[0].1.trace_id, 0x0af7651916cd43dd8448eb211c80319c);
+        assert_eq!(received[0].1.parent_span_id, Some(0xb7ad6b7169203331));
+        assert_ne!(received[1].1.trace_id, received[0].1.trace_id);
+        assert_eq!(trace::current(), None);
+    }
+
+    #[test]
+    fn publishes_and_requests_with_the_trace_context() {
+        testing::expect(
+            "default",
+            "wasmcloud:messaging",
+            "Request",
+            |args: RequestArgs| {
+                Ok(BrokerMessage {
+                    subject: "reply".to_string(),
+                    headers: args.headers,
+                    ..Default::default()
+                })
+            },
+        );
+        testing::expect(
+            "default",
+            "wasmcloud:messaging",
+            "Publish",
+            |_: PublishArgs| Ok(crate::PublishResponse { published: true }),
+        );
+        let ctx = trace::TraceContext::root();
+        trace::set_current(Some(ctx));
+        let mut headers = HashMap::new();
+        headers.insert("Traceparent".to_string(), "stale".to_string());
+        headers.insert("tenant".to_string(), "acme".to_string());
+        let reply = crate::default()
+            .request_with_headers("users".to_string(), vec![], 1000, headers)
+            .unwrap();
+        crate::default()
+            .publish("events".to_string(), "".to_string(), vec![])
+            .unwrap();
+        trace::set_current(None);
+
+        let headers = reply.headers.unwrap();
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers["traceparent"], ctx.to_string());
+        assert_eq!(headers["tenant"], "acme");
+        let args: PublishArgs = deserialize(&testing::calls()[1].payload).unwrap();
+        assert_eq!(args.headers.unwrap()["traceparent"], ctx.to_string());
+
+        // Actors built before the headers were added leave them out
+        let old = serde_json::json!({"subject": "events", "replyTo": "", "body": []});
+        let args: PublishArgs =
+            deserialize(&wasmcloud_actor_core::serialize(&old).unwrap()).unwrap();
+        assert_eq!(args.headers, None);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "guest")]
+mod broker_message_ref {
+    extern crate wapc_guest;
+    use crate::test::handle_message_lock;
+    use crate::{serialize, BrokerMessage, BrokerMessageRef, Handlers};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use wapc_guest::HandlerResult;
+    use wasmcloud_actor_core::testing;
+
+    static RECEIVED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    #[test]
+    fn borrows_body_from_payload() {
+        let _lock = handle_message_lock();
+        Handlers::register_handle_message_ref(record);
+        let mut headers = HashMap::new();
+        headers.insert("tenant".to_string(), "acme".to_string());
+        let msg = BrokerMessage {
+            subject: "events".to_string(),
+            reply_to: "replies".to_string(),
+            body: b"hello".to_vec(),
+            headers: Some(headers),
+        };
+
+        testing::invoke::<_, ()>("HandleMessage", &msg).unwrap();
+        assert_eq!(*RECEIVED.lock().unwrap(), ["events replies acme hello"]);
+
+        let payload = serialize(&msg).unwrap();
+        let borrowed: BrokerMessageRef =
+            wasmcloud_actor_core::codec::deserialize_borrowed(&payload).unwrap();
+        assert!(payload.as_ptr_range().contains(&borrowed.body.as_ptr()));
+        assert_eq!(borrowed.into_owned(), msg);
+        assert_eq!(BrokerMessageRef::from(&msg).into_owned(), msg);
+        let msg = BrokerMessage {
+            headers: None,
+            ..msg
+        };
+        assert_eq!(BrokerMessageRef::from(&msg).into_owned(), msg);
+    }
+
+    fn record(msg: BrokerMessageRef) -> HandlerResult<()> {
+        let headers = msg.headers.unwrap_or_default();
+        RECEIVED.lock().unwrap().push(format!(
+            "{} {} {} {}",
+            msg.subject,
+            msg.reply_to,
+            headers["tenant"],
+            std::str::from_utf8(msg.body)?
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,7 @@
+fn main() {
+    wasmcloud_widl_codegen::Generator::new("metrics.widl")
+        .host_role("Metrics")
+        .provider_role("Metrics", "Metrics")
+        .build()
+        .unwrap();
+}
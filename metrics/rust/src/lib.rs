@@ -0,0 +1,311 @@
+#![doc(html_logo_url = "https://avatars2.githubusercontent.com/u/52050279?s=200&v=4")]
+//! # wasmCloud Metrics Actor Interface
+//!
+//! This crate provides an abstraction over the `wasmcloud:metrics` contract. Actors record counters, gauges and
+//! histograms, each with a set of labels, through the [counter!](macro.counter.html), [gauge!](macro.gauge.html)
+//! and [histogram!](macro.histogram.html) macros. Samples are aggregated locally in a [Batch](struct.Batch.html)
+//! and written to the capability provider with a single host call at the end of every invocation.
+//!
+//! Example:
+//! ```rust
+//! extern crate wasmcloud_actor_core as actor;
+//! extern crate wasmcloud_actor_metrics as metrics;
+//! use metrics::{counter, gauge, histogram};
+//!
+//! #[actor::init]
+//! pub fn init() {
+//!     /// Writes the recorded metrics at the end of every invocation
+//!     metrics::enable_batching("default");
+//! }
+//!
+//! /// Actor must be signed with `wasmcloud:metrics` to write metrics
+//! fn record_request(route: &str, status: u16, elapsed_ms: f64, cache_entries: usize) {
+//!     counter!("requests", 1, "route" => route, "status" => status.to_string());
+//!     histogram!("request_duration_ms", elapsed_ms, "route" => route);
+//!     gauge!("cache_entries", cache_entries);
+//! }
+//! ```
+//!
+//! Counters add up the values recorded during an invocation, gauges keep the last value set and histograms keep
+//! every observed value. Without [enable_batching](fn.enable_batching.html), recorded metrics are only written
+//! when [flush](fn.flush.html) is called. Metrics are best effort: a failure to write them doesn't fail the
+//! invocation.
+//!
+//! # Capability providers
+//! The `provider` feature exposes the [Metrics](trait.Metrics.html) trait, which capability providers implement for
+//! writing metrics, and a [dispatch](fn.dispatch.html) function that routes an actor's call to it.
+
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+}
+pub use generated::*;
+
+use std::collections::{BTreeMap, HashMap};
+
+/// The operation used to write a batch of metrics
+pub const OP_WRITE_METRICS: &str = "WriteMetrics";
+
+pub const KIND_COUNTER: &str = "counter";
+pub const KIND_GAUGE: &str = "gauge";
+pub const KIND_HISTOGRAM: &str = "histogram";
+
+/// Metric samples aggregated by name, kind and labels, so that they can be written with a single host call
+#[derive(Debug, Default, Clone)]
+pub struct Batch {
+    metrics: Vec<Metric>,
+    index: HashMap<Key, usize>,
+}
+
+/// The kind, name and sorted labels of a metric
+type Key = (&'static str, String, Vec<(String, String)>);
+
+impl Batch {
+    pub fn new() -> Self {
+        Batch::default()
+    }
+
+    /// Adds `value` to a counter
+    pub fn counter(&mut self, name: &str, value: f64, labels: &[(&str, &str)]) {
+        self.entry(KIND_COUNTER, name, labels).value += value;
+    }
+
+    /// Sets the value of a gauge
+    pub fn gauge(&mut self, name: &str, value: f64, labels: &[(&str, &str)]) {
+        self.entry(KIND_GAUGE, name, labels).value = value;
+    }
+
+    /// Records a value observed by a histogram
+    pub fn histogram(&mut self, name: &str, value: f64, labels: &[(&str, &str)]) {
+        self.entry(KIND_HISTOGRAM, name, labels)
+            .observations
+            .push(value);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.metrics.is_empty()
+    }
+
+    /// Removes the aggregated metrics, in the order in which they were first recorded, leaving the batch empty
+    pub fn take(&mut self) -> Vec<Metric> {
+        self.index.clear();
+        std::mem::take(&mut self.metrics)
+    }
+
+    fn entry(&mut self, kind: &'static str, name: &str, labels: &[(&str, &str)]) -> &mut Metric {
+        // Sorting the labels makes the label set independent of the order they were given in. When a label is
+        // given twice, the last value wins
+        let labels: BTreeMap<String, String> = labels
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let key = (
+            kind,
+            name.to_string(),
+            labels.clone().into_iter().collect::<Vec<_>>(),
+        );
+        let metrics = &mut self.metrics;
+        let i = *self.index.entry(key).or_insert_with(|| {
+            metrics.push(Metric {
+                name: name.to_string(),
+                kind: kind.to_string(),
+                labels: labels.into_iter().collect(),
+                ..Default::default()
+            });
+            metrics.len() - 1
+        });
+        &mut self.metrics[i]
+    }
+}
+
+#[cfg(feature = "guest")]
+use lazy_static::lazy_static;
+#[cfg(feature = "guest")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "guest")]
+use std::sync::{Mutex, RwLock};
+#[cfg(feature = "guest")]
+use wapc_guest::{CallResult, HandlerResult};
+#[cfg(feature = "guest")]
+use wasmcloud_actor_core::interceptor::{self, Interceptor, Invocation};
+
+#[cfg(feature = "guest")]
+lazy_static! {
+    static ref BATCH: Mutex<Batch> = Mutex::new(Batch::new());
+    static ref CURRENT_BINDING: RwLock<String> = RwLock::new("default".to_string());
+}
+
+#[cfg(feature = "guest")]
+static BATCHING: AtomicBool = AtomicBool::new(false);
+
+/// Adds `value` to a counter in the actor's batch. The [counter!](macro.counter.html) macro is usually more
+/// convenient
+#[cfg(feature = "guest")]
+pub fn counter(name: &str, value: f64, labels: &[(&str, &str)]) {
+    BATCH.lock().unwrap().counter(name, value, labels);
+}
+
+/// Sets the value of a gauge in the actor's batch. The [gauge!](macro.gauge.html) macro is usually more
+/// convenient
+#[cfg(feature = "guest")]
+pub fn gauge(name: &str, value: f64, labels: &[(&str, &str)]) {
+    BATCH.lock().unwrap().gauge(name, value, labels);
+}
+
+/// Records a value observed by a histogram in the actor's batch. The [histogram!](macro.histogram.html) macro is
+/// usually more convenient
+#[cfg(feature = "guest")]
+pub fn histogram(name: &str, value: f64, labels: &[(&str, &str)]) {
+    BATCH.lock().unwrap().histogram(name, value, labels);
+}
+
+/// Writes the metrics recorded since the last flush, if any, through the binding selected with
+/// [enable_batching](fn.enable_batching.html)
+#[cfg(feature = "guest")]
+pub fn flush() -> HandlerResult<()> {
+    let metrics = BATCH.lock().unwrap().take();
+    if metrics.is_empty() {
+        return Ok(());
+    }
+    let binding = CURRENT_BINDING.read().unwrap().clone();
+    host(&binding).write_metrics(metrics)
+}
+
+/// Writes the recorded metrics through the named binding at the end of every invocation
+///
+/// This function is usually called from `init`. It registers an
+/// [interceptor](../wasmcloud_actor_core/interceptor/index.html) the first time it is called; later calls only
+/// change the binding.
+#[cfg(feature = "guest")]
+pub fn enable_batching(binding: &str) {
+    *CURRENT_BINDING.write().unwrap() = binding.to_string();
+    if !BATCHING.swap(true, Ordering::SeqCst) {
+        interceptor::register(FlushOnReturn);
+    }
+}
+
+#[cfg(feature = "guest")]
+struct FlushOnReturn;
+
+#[cfg(feature = "guest")]
+impl Interceptor for FlushOnReturn {
+    fn invocation(&self, inv: &Invocation, next: &dyn Fn(&Invocation) -> CallResult) -> CallResult {
+        let result = next(inv);
+        let _ = flush();
+        result
+    }
+}
+
+/// Adds to a counter in the actor's batch, with optional labels
+///
+/// ```
+/// # use wasmcloud_actor_metrics::counter;
+/// counter!("requests", 1);
+/// counter!("requests", 1, "route" => "/users", "method" => "GET");
+/// ```
+#[cfg(feature = "guest")]
+#[macro_export]
+macro_rules! counter {
+    ($name:expr, $value:expr $(, $key:expr => $label:expr)* $(,)?) => {
+        $crate::counter(
+            $name,
+            $value as f64,
+            &[$(($key, ::std::convert::AsRef::<str>::as_ref(&$label))),*],
+        )
+    };
+}
+
+/// Sets a gauge in the actor's batch, with optional labels
+///
+/// ```
+/// # use wasmcloud_actor_metrics::gauge;
+/// gauge!("queue_depth", 12, "queue" => "jobs");
+/// ```
+#[cfg(feature = "guest")]
+#[macro_export]
+macro_rules! gauge {
+    ($name:expr, $value:expr $(, $key:expr => $label:expr)* $(,)?) => {
+        $crate::gauge(
+            $name,
+            $value as f64,
+            &[$(($key, ::std::convert::AsRef::<str>::as_ref(&$label))),*],
+        )
+    };
+}
+
+/// Records an observation of a histogram in the actor's batch, with optional labels
+///
+/// ```
+/// # use wasmcloud_actor_metrics::histogram;
+/// histogram!("request_duration_ms", 12.5, "route" => "/users");
+/// ```
+#[cfg(feature = "guest")]
+#[macro_export]
+macro_rules! histogram {
+    ($name:expr, $value:expr $(, $key:expr => $label:expr)* $(,)?) => {
+        $crate::histogram(
+            $name,
+            $value as f64,
+            &[$(($key, ::std::convert::AsRef::<str>::as_ref(&$label))),*],
+        )
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn aggregates_samples() {
+        let mut batch = Batch::new();
+        batch.counter("requests", 1.0, &[("route", "/a"), ("method", "GET")]);
+        batch.counter("requests", 2.0, &[("method", "GET"), ("route", "/a")]);
+        batch.counter("requests", 1.0, &[("route", "/b")]);
+        batch.gauge("queue_depth", 3.0, &[]);
+        batch.gauge("queue_depth", 5.0, &[]);
+        batch.histogram("latency", 1.5, &[]);
+        batch.histogram("latency", 0.5, &[]);
+
+        let metrics = batch.take();
+        assert!(batch.is_empty());
+        assert_eq!(metrics.len(), 4);
+        assert_eq!(metrics[0].kind, KIND_COUNTER);
+        assert_eq!(metrics[0].value, 3.0);
+        assert_eq!(metrics[0].labels["route"], "/a");
+        assert_eq!(metrics[1].value, 1.0);
+        assert_eq!(metrics[2].kind, KIND_GAUGE);
+        assert_eq!(metrics[2].value, 5.0);
+        assert_eq!(metrics[3].kind, KIND_HISTOGRAM);
+        assert_eq!(metrics[3].observations, vec![1.5, 0.5]);
+    }
+
+    #[cfg(feature = "guest")]
+    #[test]
+    fn flushes_per_invocation() {
+        use wasmcloud_actor_core::testing;
+
+        enable_batching("metrics");
+        testing::expect(
+            "metrics",
+            "wasmcloud:metrics",
+            "WriteMetrics",
+            |_: WriteMetricsArgs| Ok(()),
+        );
+        let route = String::from("/users");
+        interceptor::invocation("RecordRequest", &[], |_| {
+            counter!("requests", 1, "route" => route);
+            counter!("requests", 1u64, "route" => &route);
+            histogram!("duration_ms", 12.5, "route" => "/users", "status" => 200.to_string());
+            Ok(vec![])
+        })
+        .unwrap();
+        interceptor::invocation("RecordNothing", &[], |_| Ok(vec![])).unwrap();
+
+        let calls = testing::calls();
+        assert_eq!(calls.len(), 1);
+        let args: WriteMetricsArgs = deserialize(&calls[0].payload).unwrap();
+        assert_eq!(args.metrics.len(), 2);
+        assert_eq!(args.metrics[0].value, 2.0);
+        assert_eq!(args.metrics[1].labels["status"], "200");
+        assert_eq!(args.metrics[1].observations, vec![12.5]);
+    }
+}
@@ -0,0 +1,8 @@
+fn main() {
+    wasmcloud_widl_codegen::Generator::new("telnet.widl")
+        .host_role("Server")
+        .provider_role("Server", "TelnetServer")
+        .handler_role("Client")
+        .build()
+        .unwrap();
+}
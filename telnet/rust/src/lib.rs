@@ -34,12 +34,54 @@
 //! }
 //!
 //! ```
+//!
+//! A `TelnetResult` converts to and from a `Result<(), ActorError>`, so handlers can report
+//! failures with a structured `ActorError` via `TelnetResult::from(Err(...))`, and the result
+//! of `send_text` can be checked with `IntoActorResult::into_result`.
+//!
+//! # Capability providers
+//! The `provider` feature exposes the [TelnetServer](trait.TelnetServer.html) trait, which capability providers
+//! implement for sending text to telnet sessions, and a [dispatch](fn.dispatch.html) function that routes an
+//! actor's call to it.
 
 #[allow(dead_code)]
-mod generated;
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+}
 
 pub use generated::*;
 
+use wasmcloud_actor_core::{ActorError, IntoActorResult};
+
 pub const OP_SEND_TEXT: &str = "SendText";
 pub const OP_SESSION_STARTED: &str = "SessionStarted";
 pub const OP_RECEIVE_TEXT: &str = "ReceiveText";
+
+impl IntoActorResult for TelnetResult {
+    type Ok = ();
+
+    /// Recovers the kind of the error reported by the provider, if it used the format of
+    /// `ActorError`. A failure without an error message is reported as an `Internal` error
+    fn into_result(self) -> Result<(), ActorError> {
+        match (self.success, self.error) {
+            (true, _) => Ok(()),
+            (false, Some(e)) => Err(ActorError::parse(&e)),
+            (false, None) => Err(ActorError::internal("telnet operation failed")),
+        }
+    }
+}
+
+impl From<Result<(), ActorError>> for TelnetResult {
+    fn from(res: Result<(), ActorError>) -> Self {
+        match res {
+            Ok(()) => TelnetResult {
+                success: true,
+                error: None,
+            },
+            Err(e) => TelnetResult {
+                success: false,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}